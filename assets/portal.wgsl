@@ -1,14 +1,80 @@
 #import bevy_pbr::{
     forward_io::VertexOutput,
-    mesh_view_bindings::view, 
+    mesh_view_bindings::{globals, view},
+    prepass_utils,
     utils::coords_to_viewport_uv,
+    view_transformations::depth_ndc_to_view_z,
+}
+
+struct PortalMaterial {
+    tint: vec4<f32>,
+    uv_transform: mat3x3<f32>,
+    edge_fade: f32,
+    clip_fade: f32,
+    distortion_strength: f32,
+    distortion_speed: f32,
 }
 
 @group(2) @binding(0) var base_color_texture: texture_2d<f32>;
 @group(2) @binding(1) var base_color_sampler: sampler;
+@group(2) @binding(2) var<uniform> material: PortalMaterial;
+@group(2) @binding(3) var mask_texture: texture_2d<f32>;
+@group(2) @binding(4) var mask_sampler: sampler;
+@group(2) @binding(5) var distortion_map: texture_2d<f32>;
+@group(2) @binding(6) var distortion_map_sampler: sampler;
 
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
-    let viewport_uv = coords_to_viewport_uv(mesh.position.xy, view.viewport);
-    return textureSample(base_color_texture, base_color_sampler, viewport_uv);
+    let screen_uv = coords_to_viewport_uv(mesh.position.xy, view.viewport);
+    var viewport_uv = (material.uv_transform * vec3(screen_uv, 1.0)).xy;
+
+#ifdef PORTAL_DISTORTION
+    let time = globals.time * material.distortion_speed;
+#ifdef PORTAL_DISTORTION_MAP
+    // Scroll the normal map across the mesh, using its tangent space `xy` as the offset
+#ifdef VERTEX_UVS_A
+    let map_uv = mesh.uv;
+#else
+    let map_uv = mesh.world_position.xy;
+#endif
+    let normal = textureSample(distortion_map, distortion_map_sampler, map_uv + vec2(time * 0.05));
+    let offset = normal.xy * 2.0 - 1.0;
+#else
+    // Overlapping waves travelling across the portal's surface
+    let p = mesh.world_position.xyz * 8.0;
+    let offset = vec2(
+        sin(p.x + p.z + time * 2.0) + sin(p.y * 1.3 - time * 1.7),
+        cos(p.y + p.z - time * 2.0) + cos(p.x * 1.3 + time * 1.7),
+    ) * 0.5;
+#endif
+    viewport_uv += offset * material.distortion_strength;
+#endif
+
+    var color = textureSample(base_color_texture, base_color_sampler, viewport_uv) * material.tint;
+
+#ifdef VERTEX_UVS_A
+    // Fade out towards the closest edge of the mesh
+    if material.edge_fade > 0.0 {
+        let edge_distances = min(mesh.uv, 1.0 - mesh.uv);
+        color.a *= smoothstep(0.0, material.edge_fade, min(edge_distances.x, edge_distances.y));
+    }
+
+    // Mask the portal to the shape of its opening. Without a mask, a white image is bound instead
+    color.a *= textureSample(mask_texture, mask_sampler, mesh.uv).a;
+#endif
+
+#ifdef DEPTH_PREPASS
+    // Fade out where the scene is just behind the portal, so that objects straddling its plane
+    // aren't cut off sharply
+    if material.clip_fade > 0.0 {
+        let scene_depth = prepass_utils::prepass_depth(mesh.position, 0u);
+        // A depth of zero is the far plane, where nothing was drawn
+        if scene_depth > 0.0 {
+            let distance = depth_ndc_to_view_z(mesh.position.z) - depth_ndc_to_view_z(scene_depth);
+            color.a *= smoothstep(0.0, material.clip_fade, distance);
+        }
+    }
+#endif
+
+    return color;
 }
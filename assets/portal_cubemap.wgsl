@@ -0,0 +1,59 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_view_bindings::view,
+}
+
+@group(2) @binding(0) var<uniform> portal_from_world: mat3x3<f32>;
+@group(2) @binding(1) var forward_texture: texture_2d<f32>;
+@group(2) @binding(2) var right_texture: texture_2d<f32>;
+@group(2) @binding(3) var left_texture: texture_2d<f32>;
+@group(2) @binding(4) var up_texture: texture_2d<f32>;
+@group(2) @binding(5) var down_texture: texture_2d<f32>;
+@group(2) @binding(6) var face_sampler: sampler;
+
+// Samples the face of the cubemap rendered by a camera looking along `forward` with the given `up`
+// direction, matching the `FACES` in `cubemap.rs`.
+fn sample_face(
+    face_texture: texture_2d<f32>,
+    direction: vec3<f32>,
+    forward: vec3<f32>,
+    up: vec3<f32>,
+) -> vec4<f32> {
+    let right = cross(forward, up);
+    // Each face has a 90 degree field of view, so the direction is projected onto the plane one
+    // unit in front of the camera
+    let ndc = vec2(dot(direction, right), dot(direction, up)) / dot(direction, forward);
+    let uv = vec2(ndc.x, -ndc.y) * 0.5 + 0.5;
+    // The face is picked per fragment, so the level is given explicitly to stay in uniform control
+    // flow
+    return textureSampleLevel(face_texture, face_sampler, uv, 0.0);
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let direction = portal_from_world * normalize(mesh.world_position.xyz - view.world_position);
+    let abs_direction = abs(direction);
+
+    if abs_direction.z >= abs_direction.x && abs_direction.z >= abs_direction.y {
+        if direction.z < 0.0 {
+            return sample_face(
+                forward_texture,
+                direction,
+                vec3(0.0, 0.0, -1.0),
+                vec3(0.0, 1.0, 0.0),
+            );
+        }
+        // Looking out of the back of the portal, which has no face
+        return vec4(0.0, 0.0, 0.0, 1.0);
+    }
+    if abs_direction.x >= abs_direction.y {
+        if direction.x > 0.0 {
+            return sample_face(right_texture, direction, vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0));
+        }
+        return sample_face(left_texture, direction, vec3(-1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0));
+    }
+    if direction.y > 0.0 {
+        return sample_face(up_texture, direction, vec3(0.0, 1.0, 0.0), vec3(0.0, 0.0, 1.0));
+    }
+    return sample_face(down_texture, direction, vec3(0.0, -1.0, 0.0), vec3(0.0, 0.0, -1.0));
+}
@@ -1,7 +1,7 @@
 use bevy::{color::palettes::tailwind::ORANGE_600, prelude::*};
 #[cfg(feature = "gizmos")]
 use bevy_easy_portals::gizmos::PortalGizmosPlugin;
-use bevy_easy_portals::{Portal, PortalPlugins};
+use bevy_easy_portals::{Portal, PortalCommandsExt, PortalPlugins};
 
 fn main() {
     App::new()
@@ -44,27 +44,23 @@ fn setup(
         ))
         .id();
 
-    let target_transform = Transform::from_xyz(0.0, 0.0, 2.0);
-    let target = commands.spawn(target_transform).id();
-
-    // We'll set the target relative to our shape, since that's what we want to look at
-    commands.entity(shape).add_child(target);
-
     let rectangle = Rectangle::from_size(Vec2::splat(2.5));
     let portal_transform = Transform::from_xyz(-1.5, 0.0, 0.0);
-    commands
-        .spawn((
-            // No need to spawn a material for the mesh here, it will be taken care of by the
-            // portal setup
+    // We'll set the target relative to our shape, since that's what we want to look at
+    let target_transform = Transform::from_xyz(0.0, 0.0, 2.0);
+    // No need to spawn a material for the mesh here, it will be taken care of by the portal setup
+    let (portal, _) = commands.spawn_portal_with_target(
+        meshes.add(rectangle),
+        portal_transform,
+        Portal::window(primary_camera, Entity::PLACEHOLDER),
+        target_transform,
+        Some(shape),
+    );
+    commands.entity(portal).with_children(|parent| {
+        // We can use another mesh for our portal if we wish
+        parent.spawn((
             Mesh3d(meshes.add(rectangle)),
-            portal_transform,
-            Portal::new(primary_camera, target),
-        ))
-        .with_children(|parent| {
-            // We can use another mesh for our portal if we wish
-            parent.spawn((
-                Mesh3d(meshes.add(rectangle)),
-                MeshMaterial3d(materials.add(Color::WHITE.with_alpha(0.05))),
-            ));
-        });
+            MeshMaterial3d(materials.add(Color::WHITE.with_alpha(0.05))),
+        ));
+    });
 }
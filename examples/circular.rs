@@ -0,0 +1,106 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    color::palettes::tailwind::{ORANGE_600, SKY_600},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+#[cfg(feature = "gizmos")]
+use bevy_easy_portals::gizmos::PortalGizmosPlugin;
+use bevy_easy_portals::{Portal, PortalCommandsExt, PortalPlugins};
+
+fn main() {
+    App::new()
+        .add_plugins((
+            DefaultPlugins,
+            PortalPlugins,
+            #[cfg(feature = "gizmos")]
+            PortalGizmosPlugin,
+        ))
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let primary_camera = commands
+        .spawn((
+            Camera3d::default(),
+            Camera {
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ))
+        .id();
+
+    commands.insert_resource(AmbientLight {
+        brightness: 750.0,
+        ..default()
+    });
+
+    // Something to look at through the portals, off to the side
+    let shape = commands
+        .spawn((
+            Mesh3d(meshes.add(Cuboid::default())),
+            MeshMaterial3d(materials.add(Color::from(ORANGE_600))),
+            Transform::from_xyz(0.0, -10.0, 0.0),
+        ))
+        .id();
+    commands
+        .spawn((
+            Mesh3d(meshes.add(Sphere::new(0.4))),
+            MeshMaterial3d(materials.add(Color::from(SKY_600))),
+            Transform::from_xyz(0.6, 0.6, 0.6),
+        ))
+        .set_parent(shape);
+
+    // The simplest way to get a non-rectangular opening is to give the portal a mesh of that
+    // shape, since the view is only drawn on the mesh
+    commands.spawn_portal_with_target(
+        meshes.add(Circle::new(1.5)),
+        Transform::from_xyz(-2.0, 0.0, 0.0),
+        Portal::new(primary_camera, Entity::PLACEHOLDER),
+        Transform::from_xyz(-2.0, 0.0, 4.0),
+        Some(shape),
+    );
+
+    // Shapes that are awkward to build a mesh for can instead be cut out of a rectangle with a mask
+    commands.spawn_portal_with_target(
+        meshes.add(Rectangle::new(2.0, 3.0)),
+        Transform::from_xyz(2.0, 0.0, 0.0),
+        Portal::new(primary_camera, Entity::PLACEHOLDER)
+            .with_mask(Some(images.add(doorway_mask(64, 96)))),
+        Transform::from_xyz(2.0, 0.0, 4.0),
+        Some(shape),
+    );
+}
+
+/// Creates a mask shaped like an arched doorway, whose alpha is opaque inside the opening.
+fn doorway_mask(width: u32, height: u32) -> Image {
+    let radius = width as f32 / 2.0;
+    let data = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .flat_map(|(x, y)| {
+            // Rectangular below the arch, and circular above it
+            let offset = Vec2::new(x as f32 + 0.5 - radius, (y as f32 + 0.5 - radius).min(0.0));
+            let alpha = if offset.length() <= radius { 255 } else { 0 };
+            [255, 255, 255, alpha]
+        })
+        .collect();
+
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
@@ -0,0 +1,98 @@
+use bevy::{
+    color::palettes::tailwind::{LIME_500, ORANGE_600, SKY_500},
+    prelude::*,
+};
+use bevy_easy_portals::{ui::PortalImageNode, Portal, PortalPlugins};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, PortalPlugins))
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_player)
+        .run();
+}
+
+#[derive(Component)]
+struct Player;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(AmbientLight {
+        brightness: 750.0,
+        ..default()
+    });
+
+    commands.spawn((
+        Mesh3d(meshes.add(Plane3d::default().mesh().size(30.0, 30.0))),
+        MeshMaterial3d(materials.add(Color::from(LIME_500))),
+    ));
+    let cuboid = meshes.add(Cuboid::default());
+    let cuboid_material = materials.add(Color::from(ORANGE_600));
+    for x in -2..=2 {
+        for z in -2..=2 {
+            commands.spawn((
+                Mesh3d(cuboid.clone()),
+                MeshMaterial3d(cuboid_material.clone()),
+                Transform::from_xyz(x as f32 * 5.0, 0.5, z as f32 * 5.0),
+            ));
+        }
+    }
+
+    let player = commands
+        .spawn((
+            Mesh3d(meshes.add(Sphere::new(0.5))),
+            MeshMaterial3d(materials.add(Color::from(SKY_500))),
+            Transform::from_xyz(0.0, 0.5, 0.0),
+            Player,
+        ))
+        .id();
+
+    let primary_camera = commands
+        .spawn((
+            Camera3d::default(),
+            Transform::from_xyz(0.0, 3.0, 8.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ))
+        .set_parent(player)
+        .id();
+
+    // The minimap looks straight down on the player from above, turning with them
+    let target = commands
+        .spawn(Transform::from_xyz(0.0, 15.0, 0.0).looking_to(Vec3::NEG_Y, Vec3::NEG_Z))
+        .set_parent(player)
+        .id();
+
+    // The portal doesn't need a mesh, since its image is shown by the UI instead. By keeping it
+    // just in front of the primary camera, the portal camera stays just behind (above) the target.
+    let portal = commands
+        .spawn((
+            Transform::from_xyz(0.0, 0.0, -1.0),
+            Portal::new(primary_camera, target),
+        ))
+        .set_parent(primary_camera)
+        .id();
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(16.0),
+            bottom: Val::Px(16.0),
+            width: Val::Percent(25.0),
+            // The portal's image matches the size of the primary camera's viewport, so keep its
+            // aspect ratio (that of the default window) to avoid stretching it
+            aspect_ratio: Some(16.0 / 9.0),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor(Color::WHITE),
+        PortalImageNode::new(portal),
+    ));
+}
+
+fn move_player(mut player_transform: Single<&mut Transform, With<Player>>, time: Res<Time>) {
+    let angle = time.elapsed_secs() / 4.0;
+    player_transform.translation = Vec3::new(angle.cos() * 8.0, 0.5, angle.sin() * 8.0);
+    player_transform.rotation = Quat::from_rotation_y(-angle);
+}
@@ -1,5 +1,3 @@
-use std::f32::consts::PI;
-
 use bevy::{color::palettes::tailwind::ORANGE_600, prelude::*};
 #[cfg(feature = "gizmos")]
 use bevy_easy_portals::gizmos::PortalGizmosPlugin;
@@ -14,13 +12,16 @@ fn main() {
             PortalGizmosPlugin,
         ))
         .add_systems(Startup, setup)
-        .add_systems(Update, rotate_shape)
+        .add_systems(Update, (rotate_shape, sway_mirror))
         .run();
 }
 
 #[derive(Component)]
 struct Shape;
 
+#[derive(Component)]
+struct Mirror;
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -57,12 +58,13 @@ fn setup(
 
     let rectangle = Rectangle::from_size(Vec2::splat(5.0));
 
-    let mirror = commands
+    commands
         .spawn((
             // No need to spawn a material for the mesh here, it will be taken care of by the
             // portal setup
             Mesh3d(meshes.add(rectangle)),
             Transform::from_xyz(0.0, 0.0, 0.0),
+            Mirror,
         ))
         .with_children(|parent| {
             // We can use another mesh for our mirror if we wish
@@ -71,21 +73,16 @@ fn setup(
                 MeshMaterial3d(materials.add(Color::WHITE.with_alpha(0.2))),
             ));
         })
-        .id();
-
-    // The target should be the transform of the mirror itself, but flipped
-    let target_transform = Transform::default().with_rotation(Quat::from_rotation_y(PI));
-    let target = commands.spawn(target_transform).id();
-
-    commands
-        .entity(mirror)
-        // Since we're constructing a mirror, let's parent the target to the mirror itself
-        .add_child(target)
-        // Now let's create the portal!
-        .insert(Portal::new(primary_camera, target));
+        // Now let's create the portal! A mirror's target is created for us, facing the opposite
+        // way, and kept that way however the mirror moves
+        .insert(Portal::mirror(primary_camera));
 }
 
 fn rotate_shape(mut shape_transform: Single<&mut Transform, With<Shape>>, time: Res<Time>) {
     let angle = time.delta_secs() / 2.0;
     shape_transform.rotate(Quat::from_axis_angle(Vec3::new(1.0, 1.0, 0.0), angle));
 }
+
+fn sway_mirror(mut mirror_transform: Single<&mut Transform, With<Mirror>>, time: Res<Time>) {
+    mirror_transform.rotation = Quat::from_rotation_y(time.elapsed_secs().sin() * 0.3);
+}
@@ -0,0 +1,73 @@
+use bevy::{
+    color::palettes::tailwind::{ORANGE_600, SKY_500},
+    prelude::*,
+};
+use bevy_easy_portals::{Portal, PortalPlugins};
+
+fn main() {
+    App::new()
+        .add_plugins((DefaultPlugins, PortalPlugins))
+        .add_systems(Startup, setup)
+        .add_systems(Update, rotate_shapes)
+        .run();
+}
+
+#[derive(Component)]
+struct Shape;
+
+fn setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    // Portals become 2D when their primary camera is a `Camera2d`
+    let primary_camera = commands
+        .spawn((
+            Camera2d,
+            Camera {
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+        ))
+        .id();
+
+    // Spawn a shape on each side, so the portals have something to look at
+    let left_transform = Transform::from_xyz(-300.0, 150.0, 0.0);
+    let right_transform = Transform::from_xyz(300.0, 150.0, 0.0);
+    commands.spawn((
+        Mesh2d(meshes.add(Rectangle::from_size(Vec2::splat(100.0)))),
+        MeshMaterial2d(materials.add(Color::from(ORANGE_600))),
+        left_transform,
+        Shape,
+    ));
+    commands.spawn((
+        Mesh2d(meshes.add(RegularPolygon::new(60.0, 3))),
+        MeshMaterial2d(materials.add(Color::from(SKY_500))),
+        right_transform,
+        Shape,
+    ));
+
+    // Each portal looks at the shape on the opposite side
+    let left_target = commands.spawn(right_transform).id();
+    let right_target = commands.spawn(left_transform).id();
+
+    let portal_mesh = meshes.add(Rectangle::from_size(Vec2::splat(200.0)));
+    for (translation, target) in [
+        (Vec3::new(-300.0, -150.0, 0.0), left_target),
+        (Vec3::new(300.0, -150.0, 0.0), right_target),
+    ] {
+        commands.spawn((
+            // No need to spawn a material for the mesh here, it will be taken care of by the
+            // portal setup
+            Mesh2d(portal_mesh.clone()),
+            Transform::from_translation(translation),
+            Portal::new(primary_camera, target),
+        ));
+    }
+}
+
+fn rotate_shapes(mut shape_query: Query<&mut Transform, With<Shape>>, time: Res<Time>) {
+    for mut transform in &mut shape_query {
+        transform.rotate_z(time.delta_secs());
+    }
+}
@@ -1,27 +1,30 @@
 //! Demonstrates setting up two bidirectional portals with teleportation between them.
 //!
-//! Includes basic collision handling for transitioning seamlessly between portals, a simple camera
-//! controller for movement and looking around, and a basic scene setup
+//! Includes teleporting the camera when it passes through a portal, a simple camera controller for
+//! movement and looking around, and a basic scene setup
 
 use std::f32::consts::FRAC_PI_4;
 
 use bevy::{
     color::palettes::tailwind::{SKY_200, SLATE_200},
     input::mouse::MouseMotion,
-    math::bounding::{Aabb3d, IntersectsVolume},
     prelude::*,
     render::{render_resource::Face, view::RenderLayers},
     window::{CursorGrabMode, PrimaryWindow},
 };
 #[cfg(feature = "gizmos")]
 use bevy_easy_portals::gizmos::PortalGizmosPlugin;
-use bevy_easy_portals::{camera::PortalCameraSystems, Portal, PortalPlugins};
+use bevy_easy_portals::{
+    teleport::{PortalTeleportPlugin, Teleportable},
+    Portal, PortalPlugins,
+};
 
 fn main() {
     App::new()
         .add_plugins((
             DefaultPlugins,
             PortalPlugins,
+            PortalTeleportPlugin,
             #[cfg(feature = "gizmos")]
             PortalGizmosPlugin,
         ))
@@ -30,10 +33,6 @@ fn main() {
             Update,
             (handle_camera_look, handle_movement, apply_shape_rotation),
         )
-        .add_systems(
-            PostUpdate,
-            handle_portal_collision.before(PortalCameraSystems::UpdateFrusta),
-        )
         .run();
 }
 
@@ -70,16 +69,6 @@ impl Default for CameraController {
     }
 }
 
-// Component used to track collisions between an entity and portal
-#[derive(Component, Clone, Copy)]
-#[component(storage = "SparseSet")]
-struct Collision {
-    // Offset of the entity from the portal
-    offset: Vec3,
-    // Portal entity that is being collided with
-    portal_entity: Entity,
-}
-
 // Component used to mark shapes to be rotated
 #[derive(Component)]
 struct Shape;
@@ -100,15 +89,10 @@ fn setup(
                 clear_color: ClearColorConfig::Custom(Color::BLACK),
                 ..default()
             },
-            // TODO: A not-so-nice hack to help the rendering depth conflicts between the portal's
-            // mesh and the near clipping plane of the camera. The value has been "fine-tuned" to
-            // visual correctness. PRs that remove this hack are welcome :)
-            Projection::Perspective(PerspectiveProjection {
-                near: 1e-8,
-                ..default()
-            }),
             Transform::from_translation(CAMERA_START_XYZ),
             CameraController::default(),
+            // Teleport the camera whenever it passes through a portal
+            Teleportable,
             RenderLayers::from_layers(&[0, 1]),
         ))
         .id();
@@ -171,95 +155,28 @@ fn setup(
                 Mesh3d(portal_mesh.clone()),
                 portal_transform,
                 // The mesh is a `Rectangle`, so to allow for the portal to be seen from both
-                // sides, don't cull any of its faces.
+                // sides, none of its faces are culled. The near plane normal is also flipped when
+                // we are looking at the portal's back face.
                 //
-                // We should also flip the near plane normal when we are looking at the portal's
-                // back face.
-                Portal::new(primary_camera, target)
-                    .with_cull_mode(None)
-                    .with_flip_near_plane_normal(true),
-                // Stop portals from recursively rendering eachother
+                // Portal cameras only render layer `0`, while portal meshes are on layer `1`. This
+                // stops portals from recursively rendering eachother.
+                Portal::bidirectional(primary_camera, target)
+                    .with_render_layers(Some(RenderLayers::layer(0))),
                 RenderLayers::layer(1),
             ))
             .with_children(|parent| {
                 // Portal borders
                 for (size, translation) in PORTAL_FRAME_SIZES_AND_TRANSLATIONS {
                     parent.spawn((
-                        Mesh3d(meshes.add(Cuboid::from_size(size.into()))),
+                        Mesh3d(meshes.add(Cuboid::from_size(size))),
                         MeshMaterial3d(materials.add(Color::BLACK)),
-                        Transform::from_translation(translation.into()),
+                        Transform::from_translation(translation),
                     ));
                 }
             });
     }
 }
 
-fn handle_portal_collision(
-    mut commands: Commands,
-    mut camera_query: Query<(Entity, &mut Transform), With<CameraController>>,
-    portal_query: Query<(Entity, &Portal), With<Portal>>,
-    transform_query: Query<&GlobalTransform, Without<CameraController>>,
-    mut stored_collision: Option<Single<&mut Collision>>,
-) {
-    let (camera_entity, mut camera_transform) = camera_query.get_single_mut().unwrap();
-    let camera_aabb = Aabb3d::new(camera_transform.translation, Vec3::ZERO);
-
-    for (portal_entity, portal) in &portal_query {
-        let portal_transform = transform_query.get(portal_entity).unwrap();
-        let portal_aabb = Aabb3d::new(
-            portal_transform.translation(),
-            Vec2::splat(PORTAL_MESH_SIZE).extend(1.0),
-        );
-
-        // Are we currently inside a portal?
-        if portal_aabb.intersects(&camera_aabb) {
-            let offset = camera_transform.translation - portal_transform.translation();
-
-            let Some(ref mut collision) = stored_collision else {
-                commands.entity(camera_entity).insert(Collision {
-                    offset,
-                    portal_entity,
-                });
-                return;
-            };
-
-            if collision.portal_entity == portal_entity {
-                let portal_forward = *portal_transform.forward();
-                let start_side = collision.offset.dot(portal_forward).signum();
-                let end_side = offset.dot(portal_forward).signum();
-
-                // Have we moved to the other side of the portal?
-                if start_side != end_side {
-                    let target_transform = transform_query.get(portal.target).unwrap();
-
-                    let relative_translation = portal_transform
-                        .affine()
-                        .inverse()
-                        .transform_point3(camera_transform.translation);
-                    // Now transform it back to world space using the target's transform
-                    let translation = target_transform.transform_point(relative_translation);
-
-                    let relative_rotation =
-                        portal_transform.rotation().inverse() * camera_transform.rotation;
-                    let rotation = target_transform.rotation() * relative_rotation;
-
-                    camera_transform.translation = translation;
-                    camera_transform.rotation = rotation;
-                } else {
-                    collision.offset = offset;
-                }
-            }
-        } else {
-            if stored_collision
-                .as_ref()
-                .is_some_and(|c| c.portal_entity == portal_entity)
-            {
-                commands.entity(camera_entity).remove::<Collision>();
-            }
-        };
-    }
-}
-
 fn handle_camera_look(
     mut mouse_motion_events: EventReader<MouseMotion>,
     mut camera_query: Query<(&CameraController, &mut Transform)>,
@@ -0,0 +1,93 @@
+//! Hearing audio through portals.
+//!
+//! Add the [`PortalAudioPlugin`] and insert a [`PortalAudioEmitter`] on an entity that emits audio
+//! on the far side of a [`Portal`] (i.e. around [`Portal::target`]) to have it heard as if it came
+//! through the portal's opening. The emitter's apparent position is stored in its
+//! [`PortalAudioPosition`] every frame, and applied to its [`SpatialAudioSink`] if it has one.
+//!
+//! To integrate with other audio systems, read [`PortalAudioPosition`], or remap points yourself
+//! with [`Portal::remap_point`].
+
+use bevy::{audio::DefaultSpatialScale, prelude::*};
+
+use crate::Portal;
+
+/// Enables hearing [`PortalAudioEmitter`]s through [`Portal`]s.
+pub struct PortalAudioPlugin;
+
+impl Plugin for PortalAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            update_audio_positions.after(TransformSystem::TransformPropagate),
+        )
+        // `bevy_audio` sets the positions of spatial sinks in `PostUpdate`, so they're overridden
+        // afterwards
+        .add_systems(Last, apply_audio_positions)
+        .register_type::<(PortalAudioEmitter, PortalAudioPosition)>();
+    }
+}
+
+/// Component used to mark an entity whose audio should be heard through a [`Portal`].
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component, Debug)]
+#[require(PortalAudioPosition)]
+pub struct PortalAudioEmitter {
+    /// The entity containing the [`Portal`] that the audio is heard through.
+    pub portal: Entity,
+}
+
+impl PortalAudioEmitter {
+    /// Creates a new [`PortalAudioEmitter`], heard through the given `portal`.
+    #[inline]
+    #[must_use]
+    pub fn new(portal: Entity) -> Self {
+        Self { portal }
+    }
+}
+
+/// Component that stores the world-space position a [`PortalAudioEmitter`] appears to be at when
+/// heard through its [`Portal`].
+///
+/// Updated in [`PostUpdate`], after transform propagation.
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Deref)]
+#[reflect(Component, Debug, Default)]
+pub struct PortalAudioPosition(pub Vec3);
+
+/// System that updates the [`PortalAudioPosition`] of [`PortalAudioEmitter`]s.
+fn update_audio_positions(
+    mut emitter_query: Query<(
+        &GlobalTransform,
+        &PortalAudioEmitter,
+        &mut PortalAudioPosition,
+    )>,
+    portal_query: Query<(&GlobalTransform, &Portal)>,
+    global_transform_query: Query<&GlobalTransform>,
+) {
+    for (transform, emitter, mut position) in &mut emitter_query {
+        let Ok((portal_transform, portal)) = portal_query.get(emitter.portal) else {
+            continue;
+        };
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+        let apparent_position =
+            Portal::remap_point(portal_transform, &target_transform, transform.translation());
+        position.set_if_neq(PortalAudioPosition(apparent_position));
+    }
+}
+
+/// System that applies the [`PortalAudioPosition`] of [`PortalAudioEmitter`]s to their
+/// [`SpatialAudioSink`].
+fn apply_audio_positions(
+    emitter_query: Query<
+        (&PortalAudioPosition, &SpatialAudioSink, &PlaybackSettings),
+        With<PortalAudioEmitter>,
+    >,
+    default_spatial_scale: Res<DefaultSpatialScale>,
+) {
+    for (position, sink, settings) in &emitter_query {
+        let scale = settings.spatial_scale.unwrap_or(default_spatial_scale.0).0;
+        sink.set_emitter_position(position.0 * scale);
+    }
+}
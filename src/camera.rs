@@ -1,103 +1,748 @@
+use std::{f32::consts::PI, iter, mem, time::Duration};
+
 use bevy::{
-    core_pipeline::tonemapping::{DebandDither, Tonemapping},
-    ecs::system::SystemParam,
+    asset::RenderAssetUsages,
+    core_pipeline::{
+        experimental::taa::TemporalAntiAliasing,
+        prepass::{DepthPrepass, MotionVectorPrepass, NormalPrepass},
+        tonemapping::{DebandDither, Tonemapping},
+        Skybox,
+    },
+    ecs::{query::QueryEntityError, system::SystemParam},
     image::{TextureFormatPixelInfo, Volume},
+    pbr::{
+        DistanceFog, FogFalloff, LightEntity, PbrProjectionPlugin, Shadow, SimulationLightSystems,
+        ViewLightEntities,
+    },
     prelude::*,
     render::{
-        camera::{Exposure, ManualTextureViews, RenderTarget},
-        primitives::{Frustum, HalfSpace},
+        camera::{
+            camera_system, CameraProjection, Exposure, ManualTextureViews, NormalizedRenderTarget,
+            RenderTarget,
+        },
+        primitives::{Aabb, Frustum},
+        render_phase::ViewBinnedRenderPhases,
         render_resource::{
             Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
         },
-        view::{ColorGrading, VisibilitySystems},
+        sync_world::RenderEntity,
+        view::{update_frusta, ColorGrading, ViewTarget, VisibilitySystems},
+        Extract, ExtractSchedule, Render, RenderApp, RenderSet,
     },
+    utils::{HashMap, HashSet},
     window::{PrimaryWindow, WindowRef, WindowResized},
 };
 
-use crate::Portal;
+use crate::{projection::PortalProjection, NearPlaneFlip, Portal};
+
+/// The first [`RenderLayers`] layer used for rendering [`Portal`]s recursively.
+///
+/// The meshes for the `n`th level of recursion are rendered on layer `PORTAL_RECURSION_LAYER + n`,
+/// and are only visible to the [`PortalCamera`] of the level before it.
+///
+/// See [`Portal::recursion_depth`] for more details.
+///
+/// [`RenderLayers`]: bevy::render::view::RenderLayers
+pub const PORTAL_RECURSION_LAYER: usize = 16;
+
+/// How far a visible [`Portal`]'s [`Aabb`] and [`Portal::max_render_distance`] are expanded when
+/// checking whether it should still be rendered, relative to the largest half extent and the
+/// distance respectively.
+///
+/// This prevents portals on the edge of the view (or range) from being culled and unculled every
+/// frame.
+const CULLING_MARGIN: f32 = 0.1;
+
+/// The range of distances from a [`PortalCamera`] to its oblique near plane, relative to its far
+/// plane distance.
+///
+/// A near plane that passes (almost) through the camera, or lies (almost) on the far plane, leaves
+/// no depth range to work with, resulting in inverted depth or NaNs. This happens when the primary
+/// camera is close to the portal's plane, or nearly edge-on to it.
+const NEAR_PLANE_DISTANCE_RANGE: (f32, f32) = (1e-5, 0.99);
+
+/// How often a warning is logged when near planes are clamped to [`NEAR_PLANE_DISTANCE_RANGE`].
+const NEAR_PLANE_WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often a warning is logged when [`PortalImage`]s are found missing from [`Assets<Image>`].
+const MISSING_IMAGE_WARNING_INTERVAL: Duration = Duration::from_secs(5);
 
 /// Plugin that provides [`PortalCamera`] spawning/despawning, transform and frusta updates, and
 /// resizing rendered portal images.
-pub struct PortalCameraPlugin;
+pub struct PortalCameraPlugin {
+    /// Whether to keep the [`Tonemapping`], [`DebandDither`], [`ColorGrading`], and [`Exposure`] of
+    /// [`PortalCamera`]s in sync with their [`Portal::primary_camera`].
+    ///
+    /// Disable this if you intend for the view through a portal to look different, e.g. by
+    /// modifying the [`PortalCamera`]'s components directly.
+    ///
+    /// Defaults to `true`.
+    pub sync_camera_settings: bool,
+    /// Whether to deactivate the [`PortalCamera`]s of [`Portal`]s that are out of view.
+    ///
+    /// A portal is in view if its mesh's [`Aabb`] intersects the [`Frustum`] of its
+    /// [`Portal::primary_camera`], or that of any active [`PortalCamera`] belonging to another
+    /// portal. See [`PortalCulled`] for details.
+    ///
+    /// Defaults to `true`.
+    pub frustum_culling: bool,
+    /// How long to wait after the last [`WindowResized`] event before resizing portal images.
+    ///
+    /// Resizing reallocates the images, so doing this on every event while a window is being
+    /// dragged causes hitches. Portals are stretched to fit the window until they are resized.
+    ///
+    /// This also applies to changes to the [`Camera::viewport`] of [`Portal::primary_camera`], e.g.
+    /// when split-screen viewports are updated to follow the window.
+    ///
+    /// Defaults to 100 milliseconds. Set to [`Duration::ZERO`] to resize images on the frame the
+    /// window is resized.
+    pub resize_debounce: Duration,
+}
+
+impl Default for PortalCameraPlugin {
+    fn default() -> Self {
+        Self {
+            sync_camera_settings: true,
+            frustum_culling: true,
+            resize_debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+/// Event sent after [`PortalImage`]s have been resized in response to [`WindowResized`] events, or
+/// to a change in a [`Camera::viewport`].
+///
+/// Materials that sample portal images need to be marked as changed when this happens, see
+/// [`update_materials`](crate::material::update_materials).
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PortalImagesResized;
+
+/// Event triggered on a [`Portal`] entity once its [`Portal::linked_camera`] has rendered into its
+/// [`PortalImage`] for the first time.
+///
+/// Until then, the portal shows [`Portal::placeholder_color`] (or nothing). Observe this event to
+/// e.g. fade in the portal or play a sound once it's ready, or poll the portal's
+/// [`PortalRenderedFrame`] instead.
+///
+/// The event is based on the number of frames the camera has been active for, so it may be
+/// triggered before the first frame is visible if the portal's render pipelines are still being
+/// compiled.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PortalReady;
+
+/// The number of frames a [`Portal::linked_camera`] needs to be active for before [`PortalReady`]
+/// is triggered.
+///
+/// With pipelined rendering, a frame is rendered while the next one is being updated, so the image
+/// is only guaranteed to be rendered once the second frame has been updated.
+const PORTAL_READY_FRAMES: u32 = 2;
+
+/// Component that counts the frames a [`Portal::linked_camera`] has been active for, until
+/// [`PortalReady`] is triggered.
+#[derive(Component, Debug, Default)]
+struct PendingPortalReady(u32);
+
+/// Resource that tracks the windows resized since portal images were last resized.
+#[derive(Resource, Debug, Default)]
+struct PendingPortalResize {
+    windows: HashSet<Entity>,
+    /// The new viewport size of each [`Portal::primary_camera`] whose viewport no longer matches
+    /// its portals' images.
+    viewports: HashMap<Entity, UVec2>,
+    last_resized: Duration,
+    debounce: Duration,
+}
 
 /// Label for systems that update [`Portal`] related cameras.
 #[derive(Debug, PartialEq, Eq, Clone, Hash, SystemSet)]
 pub enum PortalCameraSystems {
-    /// Resizes [`Portal::linked_camera`]'s rendered image if any [`WindowResized`] events are read.
+    /// Resizes [`Portal::linked_camera`]'s rendered image once [`WindowResized`] events (or
+    /// [`Camera::viewport`] changes) have settled, see [`PortalCameraPlugin::resize_debounce`].
     ResizeImage,
+    /// Moves entities through [`Portal`]s, e.g. `teleport::PortalTeleportPlugin`'s teleportation
+    /// with the `teleport` feature.
+    ///
+    /// Runs in [`PostUpdate`] before [`TransformSystem::TransformPropagate`], and therefore before
+    /// [`PortalCameraSystems::UpdateTransform`] and [`PortalCameraSystems::UpdateFrusta`]. Custom
+    /// logic that moves an entity through a portal (e.g. collision handling, or a camera being
+    /// carried through) should run in this set, so that [`PortalCamera`]s are placed relative to
+    /// the entity's new position in the same frame, rather than rendering a frame from the old one.
+    Teleport,
     /// Updates the [`GlobalTransform`] and [`Transform`] components for [`Portal::linked_camera`]
     /// based on the [`Portal::primary_camera`]s [`GlobalTransform`].
+    ///
+    /// Runs in [`PostUpdate`] after [`TransformSystem::TransformPropagate`], so the final
+    /// [`GlobalTransform`]s of the frame are used. A [`Portal::target`] (or portal, or primary
+    /// camera) that is moved any time before propagation, whether in [`Update`], [`FixedUpdate`],
+    /// or by an animation in [`PostUpdate`], is therefore reflected by the portal in the same frame
+    /// rather than lagging behind. Systems that write a [`GlobalTransform`] directly after
+    /// propagation should run before this set.
     UpdateTransform,
-    /// Updates the [`Frustum`] for [`Portal::linked_camera`].
+    /// Updates the [`PortalProjection`] of [`Portal::linked_camera`] (and any
+    /// [`PortalRecursion::cameras`]), applying [`Portal::projection`] and then placing its oblique
+    /// near plane on the plane of [`Portal::target`].
+    ///
+    /// Runs in [`PostUpdate`] after [`PortalCameraSystems::UpdateTransform`], since the near plane
+    /// depends on the camera's final transform, and before [`PortalCameraSystems::UpdateFrusta`].
+    /// Custom projection logic (e.g. a different field of view through the portal) should run
+    /// after this set and before [`PortalCameraSystems::UpdateFrusta`], so that it's picked up in
+    /// the same frame without being overwritten.
+    UpdateProjection,
+    /// Computes the [`Camera`] matrices and [`Frustum`] of [`Portal::linked_camera`] from its
+    /// [`PortalProjection`], such that its near plane lies on the plane of [`Portal::target`].
+    ///
+    /// Runs in [`PostUpdate`] after [`PortalCameraSystems::UpdateProjection`].
+    ///
+    /// [`Frustum`]: bevy::render::primitives::Frustum
     UpdateFrusta,
+    /// Updates the [`PortalCulled`] state of [`Portal`]s based on whether their mesh is in view,
+    /// and whether they are within [`Portal::max_render_distance`].
+    ///
+    /// Frustum culling only happens if [`PortalCameraPlugin::frustum_culling`] is enabled.
+    UpdateCulling,
+    /// Copies any changes to the [`Tonemapping`], [`DebandDither`], [`ColorGrading`], and
+    /// [`Exposure`] of [`Portal::primary_camera`] to its [`PortalCamera`]s.
+    ///
+    /// Only present if [`PortalCameraPlugin::sync_camera_settings`] is enabled.
+    SyncCameraSettings,
 }
 
 impl Plugin for PortalCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.configure_sets(
-            PostUpdate,
-            (
-                PortalCameraSystems::UpdateTransform.after(TransformSystem::TransformPropagate),
-                PortalCameraSystems::UpdateFrusta.after(VisibilitySystems::UpdateFrusta),
+        app.add_plugins(PbrProjectionPlugin::<PortalProjection>::default())
+            .configure_sets(
+                PostUpdate,
+                (
+                    PortalCameraSystems::Teleport.before(TransformSystem::TransformPropagate),
+                    PortalCameraSystems::UpdateTransform
+                        .after(TransformSystem::TransformPropagate)
+                        .before(VisibilitySystems::UpdateFrusta),
+                    PortalCameraSystems::UpdateProjection,
+                    PortalCameraSystems::UpdateFrusta
+                        .after(VisibilitySystems::UpdateFrusta)
+                        .before(VisibilitySystems::CheckVisibility)
+                        .before(SimulationLightSystems::UpdateDirectionalLightCascades),
+                    PortalCameraSystems::UpdateCulling.after(VisibilitySystems::CalculateBounds),
+                )
+                    .chain(),
             )
-                .chain(),
-        )
-        .add_systems(
-            PreUpdate,
-            resize_portal_images.in_set(PortalCameraSystems::ResizeImage),
-        )
-        .add_systems(
+            .add_systems(
+                PreUpdate,
+                (
+                    increment_portal_frame_count,
+                    retry_portal_setup,
+                    (resize_portal_images, resize_changed_portal_images)
+                        .in_set(PortalCameraSystems::ResizeImage),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (update_portal_camera_active, update_portal_camera_interval)
+                        .chain()
+                        .after(PortalCameraSystems::UpdateCulling),
+                    relink_portal_cameras.before(PortalCameraSystems::UpdateTransform),
+                    (update_mirror_targets, update_portal_camera_transform)
+                        .chain()
+                        .in_set(PortalCameraSystems::UpdateTransform),
+                    update_portal_camera_fog.after(PortalCameraSystems::UpdateTransform),
+                    // The portal camera's transform is only known after transform propagation, so
+                    // the projection (and in turn, the frustum) needs to be updated after it
+                    (
+                        update_portal_camera_projection,
+                        update_portal_camera_near_plane,
+                    )
+                        .chain()
+                        .in_set(PortalCameraSystems::UpdateProjection),
+                    (
+                        camera_system::<PortalProjection>,
+                        update_frusta::<PortalProjection>,
+                    )
+                        .chain()
+                        .in_set(PortalCameraSystems::UpdateFrusta),
+                ),
+            )
+            .add_systems(
+                Last,
+                (update_portal_rendered_frames, trigger_portal_ready).chain(),
+            )
+            .init_resource::<PortalCameraOrder>()
+            .init_resource::<PortalFrameCount>()
+            .init_resource::<PortalImagePool>()
+            .insert_resource(PendingPortalResize {
+                debounce: self.resize_debounce,
+                ..default()
+            })
+            .add_event::<PortalImagesResized>()
+            .add_observer(setup_portal_camera::<OnAdd, Portal>)
+            .add_observer(setup_portal_camera::<RetryPortalSetup, ()>)
+            .add_observer(despawn_portal_camera)
+            .add_observer(disable_orphaned_portals)
+            .register_type::<(
+                PortalCamera,
+                PortalCulled,
+                PortalImage,
+                PortalProjection,
+                PortalRecursion,
+                PortalRenderedFrame,
+                PortalView,
+            )>();
+
+        app.add_systems(
             PostUpdate,
-            (
-                update_portal_camera_transform.in_set(PortalCameraSystems::UpdateTransform),
-                update_portal_camera_frusta.in_set(PortalCameraSystems::UpdateFrusta),
-            ),
-        )
-        .add_observer(setup_portal_camera)
-        .add_observer(despawn_portal_camera)
-        .register_type::<(PortalCamera, PortalImage)>();
+            cull_distant_portals.in_set(PortalCameraSystems::UpdateCulling),
+        );
+        if self.frustum_culling {
+            app.add_systems(
+                PostUpdate,
+                cull_portals.in_set(PortalCameraSystems::UpdateCulling),
+            );
+        }
+
+        if self.sync_camera_settings {
+            app.add_systems(
+                PostUpdate,
+                sync_portal_camera_settings.in_set(PortalCameraSystems::SyncCameraSettings),
+            );
+        }
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_systems(ExtractSchedule, extract_portal_shadows)
+            .add_systems(
+                Render,
+                clear_portal_shadow_phases.in_set(RenderSet::PhaseSort),
+            );
+    }
+}
+
+/// Resource used to assign each [`PortalCamera`] a unique [`Camera::order`].
+///
+/// Stores how many orders have been handed out so far, so that no two portals render with the same
+/// order.
+#[derive(Resource, Debug, Default)]
+pub(crate) struct PortalCameraOrder(pub(crate) isize);
+
+/// Resource that counts the frames since [`PortalCameraPlugin`] was added, deciding which portals
+/// render on which frame (see [`Portal::render_interval`]).
+///
+/// It's incremented at the start of every [`App::update`], in [`PreUpdate`], so the first frame is
+/// `1`, and stepping an app one update at a time (e.g. in tests) steps it one frame at a time. This
+/// keeps the frames portals render on deterministic, regardless of the wall-clock time.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct PortalFrameCount(u32);
+
+impl PortalFrameCount {
+    /// Returns the number of the current frame, wrapping around to `0` after [`u32::MAX`].
+    #[inline]
+    pub fn get(&self) -> u32 {
+        self.0
     }
 }
 
+/// System that increments the [`PortalFrameCount`] at the start of each frame.
+fn increment_portal_frame_count(mut frame_count: ResMut<PortalFrameCount>) {
+    frame_count.0 = frame_count.0.wrapping_add(1);
+}
+
+/// Resource that keeps the images of despawned [`Portal`]s around, so that they can be reused by
+/// portals spawned later on.
+///
+/// Images are only reused for portals with the same image size and format. This avoids allocating
+/// new images when portals are frequently spawned and despawned, e.g. when streaming levels.
+#[derive(Resource, Debug)]
+pub struct PortalImagePool {
+    /// The maximum number of images kept in the pool. Images released while the pool is full are
+    /// freed instead.
+    ///
+    /// Set to `0` to disable pooling.
+    ///
+    /// Defaults to `8`.
+    pub max_size: usize,
+    images: HashMap<(Extent3d, TextureFormat), Vec<Handle<Image>>>,
+}
+
+impl Default for PortalImagePool {
+    fn default() -> Self {
+        Self {
+            max_size: 8,
+            images: HashMap::default(),
+        }
+    }
+}
+
+impl PortalImagePool {
+    /// Returns the number of images currently in the pool.
+    pub fn len(&self) -> usize {
+        self.images.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the pool contains no images.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all images from the pool, allowing them to be freed.
+    pub fn clear(&mut self) {
+        self.images.clear();
+    }
+
+    /// Takes an image with the given `size` and `format` out of the pool, if there is one.
+    fn take(&mut self, size: Extent3d, format: TextureFormat) -> Option<Handle<Image>> {
+        self.images.get_mut(&(size, format))?.pop()
+    }
+
+    /// Adds `handle` to the pool, unless the pool is full.
+    fn release(&mut self, size: Extent3d, format: TextureFormat, handle: Handle<Image>) {
+        if self.len() < self.max_size {
+            self.images.entry((size, format)).or_default().push(handle);
+        }
+    }
+}
+
+/// Which components of [`Portal::primary_camera`] are inherited by a [`Portal`]'s
+/// [`PortalCamera`]s, in addition to those that are always inherited.
+///
+/// Opt out of inheriting a component if the scene seen through the portal should look different,
+/// e.g. if it has its own skybox.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortalInheritance {
+    /// Whether to inherit the [`Skybox`].
+    ///
+    /// Defaults to `true`.
+    pub skybox: bool,
+    /// Whether to inherit the [`EnvironmentMapLight`].
+    ///
+    /// Defaults to `true`.
+    pub environment_map: bool,
+    /// Whether to inherit the [`DistanceFog`].
+    ///
+    /// Defaults to `true`.
+    pub fog: bool,
+    /// How the inherited [`DistanceFog`] is measured through the portal, see [`PortalFogMode`].
+    ///
+    /// Defaults to [`PortalFogMode::WindowConsistent`].
+    pub fog_mode: PortalFogMode,
+    /// Whether to inherit the [`DepthPrepass`], needed for effects like SSAO inside the portal.
+    ///
+    /// Each prepass is an extra pass for every [`PortalCamera`] (including one per level of
+    /// [`Portal::recursion_depth`]), so consider opting out for portals that don't need it.
+    ///
+    /// Defaults to `true`.
+    pub depth_prepass: bool,
+    /// Whether to inherit the [`NormalPrepass`], needed for effects like SSAO and SSR inside the
+    /// portal.
+    ///
+    /// Like [`PortalInheritance::depth_prepass`], this adds to the cost of every [`PortalCamera`].
+    ///
+    /// Defaults to `true`.
+    pub normal_prepass: bool,
+    /// Whether to inherit the [`MotionVectorPrepass`], needed for effects like motion blur inside
+    /// the portal.
+    ///
+    /// Like [`PortalInheritance::depth_prepass`], this adds to the cost of every [`PortalCamera`].
+    ///
+    /// Defaults to `true`.
+    pub motion_vector_prepass: bool,
+}
+
+impl Default for PortalInheritance {
+    fn default() -> Self {
+        Self {
+            skybox: true,
+            environment_map: true,
+            fog: true,
+            fog_mode: PortalFogMode::default(),
+            depth_prepass: true,
+            normal_prepass: true,
+            motion_vector_prepass: true,
+        }
+    }
+}
+
+/// How the [`DistanceFog`] inherited by a [`Portal`]'s [`PortalCamera`]s is measured, see
+/// [`PortalInheritance::fog_mode`].
+#[derive(Reflect, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PortalFogMode {
+    /// Fog is measured from the [`PortalCamera`], like any other camera.
+    ///
+    /// The portal camera is as far behind [`Portal::target`] as the primary camera is in front of
+    /// the portal, so geometry seen through the portal is fogged as if the portal were a window at
+    /// the player's distance. The fog thickens continuously across the portal's edge, blending
+    /// with the primary camera's view.
+    #[default]
+    WindowConsistent,
+    /// Fog is measured from [`Portal::target`], by pushing the fog's start and end out by the
+    /// distance between the [`PortalCamera`] and the target every frame.
+    ///
+    /// The view through the portal is fogged as it would be for someone standing at the target,
+    /// regardless of how far the player is from the portal. This suits portals into distant places
+    /// with an atmosphere of their own, but the fog no longer matches across the portal's edge.
+    ///
+    /// Only a [`FogFalloff::Linear`] can be shifted this way. Other falloffs are measured from the
+    /// [`PortalCamera`], as with [`PortalFogMode::WindowConsistent`].
+    PhysicallyAccurate,
+}
+
 /// Component used to mark a [`Portal`]'s associated camera.
+///
+/// Portal cameras are named after their portal, e.g. `Portal Camera (12v1)`. To customize them,
+/// e.g. to give them a name of your own or tag them with a marker component, observe this
+/// component being added:
+///
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_easy_portals::camera::PortalCamera;
+/// #[derive(Component)]
+/// struct SecurityCamera;
+///
+/// fn tag_portal_cameras(
+///     trigger: Trigger<OnAdd, PortalCamera>,
+///     mut commands: Commands,
+///     portal_camera_query: Query<&PortalCamera>,
+///     name_query: Query<&Name>,
+/// ) {
+///     let portal = portal_camera_query.get(trigger.entity()).unwrap().0;
+///     if let Ok(name) = name_query.get(portal) {
+///         commands
+///             .entity(trigger.entity())
+///             .insert((Name::new(format!("{name} Camera")), SecurityCamera));
+///     }
+/// }
+///
+/// App::new().add_observer(tag_portal_cameras);
+/// ```
 #[derive(Component, Reflect, Debug)]
 #[reflect(Component)]
 pub struct PortalCamera(pub Entity);
 
 /// Component used to store a weak reference to a [`PortalCamera`]'s rendered image.
+///
+/// This is present on both the [`Portal`] entity, referencing the image of
+/// [`Portal::linked_camera`], and on each [`PortalCamera`] entity, referencing the image it renders
+/// to.
 #[derive(Component, Reflect, Debug, Deref, DerefMut)]
 #[reflect(Component)]
 pub struct PortalImage(pub Handle<Image>);
 
-/// System that is triggered whenever a [`Portal`] component is added to an entity.
+/// Component on a [`Portal`] that indicates whether it is culled, in which case its
+/// [`PortalCamera`]s are deactivated.
+///
+/// Once rendered, a portal needs to move out of view (or range) by a small margin before it is
+/// culled again, so that it doesn't flicker when it is on the edge.
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct PortalCulled {
+    /// Whether the portal is out of view.
+    ///
+    /// Only updated if [`PortalCameraPlugin::frustum_culling`] is enabled, and if the portal has
+    /// an [`Aabb`] (i.e. it has a mesh, and no [`NoFrustumCulling`]).
+    ///
+    /// [`NoFrustumCulling`]: bevy::render::view::NoFrustumCulling
+    pub out_of_view: bool,
+    /// Whether the portal is farther than [`Portal::max_render_distance`] from
+    /// [`Portal::primary_camera`].
+    pub out_of_range: bool,
+}
+
+impl PortalCulled {
+    /// Returns `true` if the portal is either out of view or out of range.
+    #[inline]
+    pub fn is_culled(&self) -> bool {
+        self.out_of_view || self.out_of_range
+    }
+}
+
+/// Component on a [`Portal`] that stores the last frame its [`Portal::linked_camera`] rendered into
+/// its [`PortalImage`], as a [`PortalFrameCount`].
+///
+/// This tells whether the portal has produced a real frame yet (e.g. to show a fallback until it
+/// has), and how stale its image is when it's culled or rendered every few frames (see
+/// [`Portal::render_interval`]). A frame counts as rendered if the camera is active at the end of
+/// it. With pipelined rendering, the image is only written while the next frame is updated.
+///
+/// The frame is a `u32` to match [`PortalFrameCount`] (and Bevy's [`FrameCount`]), so it wraps
+/// around to `0` after [`u32::MAX`] frames, which takes over two years at 60 frames per second.
+/// [`PortalRenderedFrame::frames_since_rendered`] uses wrapping arithmetic, so it stays correct
+/// across the wrap as long as the portal was rendered within the last [`u32::MAX`] frames.
+///
+/// [`FrameCount`]: bevy::core::FrameCount
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[reflect(Component, Default)]
+pub struct PortalRenderedFrame(Option<u32>);
+
+impl PortalRenderedFrame {
+    /// Returns the [`PortalFrameCount`] of the last frame the portal was rendered on, if any.
+    #[inline]
+    pub fn get(&self) -> Option<u32> {
+        self.0
+    }
+
+    /// Returns `true` if the portal has been rendered at least once.
+    #[inline]
+    pub fn has_rendered(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Returns the number of frames since the portal was last rendered, given the current
+    /// `frame_count`, or `None` if it hasn't been rendered yet.
+    #[inline]
+    pub fn frames_since_rendered(&self, frame_count: &PortalFrameCount) -> Option<u32> {
+        self.0.map(|frame| frame_count.get().wrapping_sub(frame))
+    }
+}
+
+/// Component on a [`Portal`] that stores the world-space transform of its
+/// [`Portal::linked_camera`], i.e. where the view through the portal is seen from.
+///
+/// This lets gameplay systems (e.g. AI that sees through portals, or audio) follow the view without
+/// looking up the camera. It's updated in [`PortalCameraSystems::UpdateTransform`], and changing it
+/// doesn't move the camera.
+#[derive(Component, Reflect, Debug, Default, Clone, Copy, PartialEq)]
+#[reflect(Component, Default)]
+pub struct PortalView {
+    /// The position of the view.
+    pub translation: Vec3,
+    /// The rotation of the view.
+    pub rotation: Quat,
+}
+
+impl PortalView {
+    /// Returns the direction the view is looking in.
+    #[inline]
+    pub fn forward(&self) -> Dir3 {
+        self.rotation * Dir3::NEG_Z
+    }
+}
+
+/// Component used to store the nested [`PortalCamera`]s, and weak references to their rendered
+/// images, of a [`Portal`] with a [`Portal::recursion_depth`] greater than `0`.
+///
+/// The elements at index `n` correspond to level `n + 1` of recursion, with level `0` being
+/// [`Portal::linked_camera`] and [`PortalImage`].
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component)]
+pub struct PortalRecursion {
+    /// The nested [`PortalCamera`]s, ordered by level.
+    pub cameras: Vec<Entity>,
+    /// Weak references to the images rendered by [`PortalRecursion::cameras`].
+    pub images: Vec<Handle<Image>>,
+}
+
+/// System that is triggered whenever a [`Portal`] component is added to an entity, or when its
+/// setup is retried (see [`RetryPortalSetup`]).
 ///
 /// An image is created based on the primary camera's viewport size. Then, a [`PortalCamera`] is
 /// created, with [`Camera::target`] set to render the [`PortalCamera`]'s view to the image.
 ///
+/// If the viewport size can't be obtained yet (e.g. the primary window hasn't been created), the
+/// portal is marked with [`PendingPortalSetup`] and set up again by [`retry_portal_setup`] once it
+/// can.
+///
 /// # Notes
 ///
-/// * The [`PortalCamera`] will inherit any properties currently present on the primary camera.
-fn setup_portal_camera(
-    trigger: Trigger<OnAdd, Portal>,
+/// * The [`PortalCamera`] will inherit any properties currently present on the primary camera,
+///   except for its [`Camera::viewport`], since the image is sized to match that viewport, and its
+///   [`Camera::clear_color`] if overridden by [`Portal::clear_color`].
+/// * The primary camera's [`Projection`] is wrapped in a [`PortalProjection`], unless overridden by
+///   [`Portal::projection`].
+/// * If [`Portal::target`] is [`Entity::PLACEHOLDER`] (see [`Portal::mirror`]) and there's no
+///   [`Portal::target_transform`], a target is spawned as a child of the portal.
+/// * Each [`PortalCamera`] is given a unique [`Camera::order`] lower than the primary camera's.
+/// * The primary camera's [`Msaa`] is inherited, but the image is created with a `sample_count` of
+///   `1`. Multisampling happens on the camera's main texture, which Bevy resolves into the image.
+/// * If [`Portal::recursion_depth`] is greater than `0`, a [`PortalCamera`] and image are created
+///   for each level of recursion, and stored in [`PortalRecursion`]. Recursion is skipped (and an
+///   error logged) if [`Portal::render_layers`] isn't set, since the portal's cameras would then
+///   render the portal's own mesh.
+/// * The primary camera's [`Skybox`], [`EnvironmentMapLight`], [`DistanceFog`], and prepasses
+///   ([`DepthPrepass`], [`NormalPrepass`], and [`MotionVectorPrepass`]) are inherited according to
+///   [`Portal::inheritance`]. Prepasses aren't inherited by 2D portals.
+/// * The primary camera's [`TemporalAntiAliasing`] is *not* inherited, since its jitter and history
+///   don't account for the remapped transform of the [`PortalCamera`]. A warning is logged if the
+///   primary camera uses it, as the portal's surface may show ghosting.
+/// * If the primary camera is a [`Camera2d`], a [`Camera2d`] is spawned instead, inheriting the
+///   primary camera's [`OrthographicProjection`]. Recursion isn't supported for 2D portals.
+/// * If [`Portal::linked_camera`] is already set to an existing entity (see
+///   [`spawn_portal_with_camera`]), the [`PortalCamera`] is inserted on that entity rather than
+///   spawned.
+/// * A portal whose [`Portal::target`] is itself, or another portal targeting it in turn, isn't set
+///   up and an error is logged, since its camera would be placed relative to its own transform. The
+///   other portal is left as it is.
+///   Paired portals should target separate entities, e.g. children of each other rotated to face
+///   away.
+///
+/// [`spawn_portal_with_camera`]: crate::PortalCommandsExt::spawn_portal_with_camera
+fn setup_portal_camera<E: Event, B: Bundle>(
+    trigger: Trigger<E, B>,
     mut commands: Commands,
     mut portal_query: Query<&mut Portal>,
     primary_camera_query: Query<(
         &Camera,
         Option<&Camera3d>,
+        Option<&Projection>,
+        Option<&Camera2d>,
+        Option<&OrthographicProjection>,
         Option<&DebandDither>,
         Option<&Tonemapping>,
         Option<&ColorGrading>,
         Option<&Exposure>,
+        Option<&Msaa>,
+    )>,
+    inherited_query: Query<(
+        Option<&Skybox>,
+        Option<&EnvironmentMapLight>,
+        Option<&DistanceFog>,
+        Has<TemporalAntiAliasing>,
+        (
+            Has<DepthPrepass>,
+            Has<NormalPrepass>,
+            Has<MotionVectorPrepass>,
+        ),
     )>,
     global_transform_query: Query<&GlobalTransform>,
     mut portal_images: PortalImages,
+    mut camera_order: ResMut<PortalCameraOrder>,
 ) {
     let entity = trigger.entity();
 
+    let portal = portal_query.get(entity).unwrap();
+    if portal.target == entity {
+        error!(
+            "could not setup portal camera {entity}: the portal targets itself, use a separate \
+            target entity instead"
+        );
+        return;
+    }
+    if portal_query
+        .get(portal.target)
+        .is_ok_and(|target_portal| target_portal.target == entity)
+    {
+        error!(
+            "could not setup portal camera {entity}: the portal targets portal {target}, which \
+            targets it in turn; give each portal a separate target entity instead, e.g. a child of \
+            the other portal (portal {target} is left enabled, showing the view from this portal)",
+            target = portal.target
+        );
+        return;
+    }
+
     let mut portal = portal_query.get_mut(entity).unwrap();
 
-    let Ok((primary_camera, camera_3d, tonemapping, deband_dither, color_grading, exposure)) =
-        primary_camera_query.get(portal.primary_camera)
+    let Ok((
+        primary_camera,
+        camera_3d,
+        projection,
+        camera_2d,
+        orthographic_projection,
+        tonemapping,
+        deband_dither,
+        color_grading,
+        exposure,
+        msaa,
+    )) = primary_camera_query.get(portal.primary_camera)
     else {
         error!(
             "could not setup portal camera {entity}: primary_camera does not contain a Camera component"
@@ -105,153 +750,1407 @@ fn setup_portal_camera(
         return;
     };
 
-    let Some(image_handle) = portal_images.new(primary_camera) else {
-        error!("could not create portal image for {entity}");
-        return;
+    let (
+        skybox,
+        environment_map_light,
+        distance_fog,
+        has_taa,
+        (depth_prepass, normal_prepass, motion_vector_prepass),
+    ) = inherited_query
+        .get(portal.primary_camera)
+        .expect("primary camera should exist");
+    if has_taa {
+        warn!(
+            "primary camera of portal {entity} uses TemporalAntiAliasing, which isn't supported \
+            for portal cameras; the view through the portal is rendered without it and may show \
+            ghosting"
+        );
+    }
+    // XR runtimes hand each eye's swapchain image to Bevy as a manual texture view
+    if matches!(primary_camera.target, RenderTarget::TextureView(_)) {
+        warn!(
+            "primary camera of portal {entity} renders to a texture view, such as an XR eye; the \
+            view through the portal is rendered in mono from this camera only, spawn a portal for \
+            each eye's camera to render it in stereo"
+        );
+    }
+    let inheritance = portal.inheritance;
+
+    let render_target = portal.render_target.clone().filter(|render_target| {
+        let is_window = matches!(render_target, RenderTarget::Window(_));
+        if is_window {
+            warn!("portal {entity} has a window as its render_target, which isn't supported");
+        }
+        !is_window
+    });
+
+    let is_2d = camera_2d.is_some() && camera_3d.is_none();
+    let recursion_depth = if is_2d && portal.recursion_depth > 0 {
+        warn!("portal {entity} has a recursion_depth, but recursion isn't supported in 2D");
+        0
+    } else if render_target.is_some() && portal.recursion_depth > 0 {
+        warn!(
+            "portal {entity} has a recursion_depth, but recursion isn't supported with a \
+            render_target"
+        );
+        0
+    } else if portal.render_layers.is_none() && portal.recursion_depth > 0 {
+        error!(
+            "portal {entity} has a recursion_depth, but no render_layers; its cameras would render \
+            the portal's own mesh, which samples the images they render to. Set render_layers to \
+            layers that don't include the portal's mesh to enable recursion"
+        );
+        0
+    } else {
+        portal.recursion_depth
     };
 
-    let Ok(global_transform) = global_transform_query.get(portal.target).copied() else {
-        error!("portal target is missing a GlobalTransform");
+    // One render target for each level of recursion, with the first being rendered by the linked
+    // camera
+    let mut targets = Vec::with_capacity(usize::from(recursion_depth) + 1);
+    if let Some(render_target) = render_target {
+        targets.push(render_target);
+    } else {
+        for _ in 0..=recursion_depth {
+            let Some(image_handle) = portal_images.create(primary_camera, &portal) else {
+                warn!(
+                    "could not create portal image for {entity} yet, retrying once its primary \
+                    camera has a viewport size"
+                );
+                commands.entity(entity).insert(PendingPortalSetup);
+                return;
+            };
+            targets.push(RenderTarget::Image(image_handle));
+        }
+    }
+    let images = targets
+        .iter()
+        .map(|target| match target {
+            RenderTarget::Image(image_handle) => Some(image_handle.clone_weak()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Mirrors created with `Portal::mirror` need a target, facing the opposite direction
+    let global_transform = if let Some(target_transform) = portal.target_transform {
+        Ok(GlobalTransform::from(target_transform))
+    } else if portal.target == Entity::PLACEHOLDER {
+        portal.target = commands
+            .spawn((Name::new("Portal Mirror Target"), mirror_target_transform()))
+            .set_parent(entity)
+            .id();
+
+        global_transform_query
+            .get(entity)
+            .map(|portal_transform| portal_transform.mul_transform(mirror_target_transform()))
+    } else {
+        match global_transform_query.get(portal.target) {
+            Ok(&global_transform) => Ok(global_transform),
+            // Forgetting the target's `Transform` is a common mistake, so give it one rather than
+            // failing
+            Err(QueryEntityError::QueryDoesNotMatch(..)) => {
+                warn!(
+                    "target {} of portal {entity} has no Transform, inserting a default one",
+                    portal.target
+                );
+                commands.entity(portal.target).insert(Transform::default());
+                Ok(GlobalTransform::IDENTITY)
+            }
+            Err(error) => Err(error),
+        }
+    };
+    let Ok(global_transform) = global_transform else {
+        error!(
+            "could not setup portal camera {entity}: target {} does not exist",
+            portal.target
+        );
         return;
     };
-    portal.linked_camera = Some(
-        commands
-            .spawn((
-                Name::new("Portal Camera"),
-                Camera {
-                    order: -1,
-                    target: RenderTarget::Image(image_handle.clone()),
-                    ..primary_camera.clone()
-                },
-                global_transform.compute_transform(),
-                global_transform,
-                camera_3d.cloned().unwrap_or_default(),
-                tonemapping.copied().unwrap_or_default(),
-                deband_dither.copied().unwrap_or_default(),
-                color_grading.cloned().unwrap_or_default(),
-                exposure.copied().unwrap_or_default(),
-                PortalCamera(entity),
-            ))
-            .id(),
-    );
 
-    commands
-        .entity(entity)
-        .insert(PortalImage(image_handle.clone_weak()));
+    // Portal cameras render before the primary camera, with each portal using a distinct range of
+    // orders to avoid ambiguities
+    let base_order = primary_camera.order - 1 - camera_order.0;
+    camera_order.0 += targets.len() as isize;
+
+    let reserved_camera = portal
+        .linked_camera
+        .filter(|&camera| commands.get_entity(camera).is_some());
+
+    let mut cameras = Vec::with_capacity(targets.len());
+    for (level, (target, image_handle)) in targets.into_iter().zip(&images).enumerate() {
+        let mut portal_camera = match reserved_camera.filter(|_| level == 0) {
+            Some(camera) => commands.entity(camera),
+            None => commands.spawn_empty(),
+        };
+        // A camera reserved by the user may already have a name of its own
+        portal_camera.insert_if_new(if level == 0 {
+            Name::new(format!("Portal Camera ({entity})"))
+        } else {
+            Name::new(format!("Portal Camera ({entity}, level {level})"))
+        });
+        portal_camera.insert((
+            Camera {
+                // Deeper levels need to be rendered first, since they are visible to the levels
+                // before them
+                order: base_order - level as isize,
+                target,
+                // The image is already the size of the primary camera's viewport, so the portal
+                // camera renders to all of it
+                viewport: None,
+                clear_color: portal.clear_color.unwrap_or(primary_camera.clear_color),
+                ..primary_camera.clone()
+            },
+            global_transform.compute_transform(),
+            global_transform,
+            tonemapping.copied().unwrap_or_default(),
+            deband_dither.copied().unwrap_or_default(),
+            color_grading.cloned().unwrap_or_default(),
+            portal_exposure(exposure, &portal),
+            // The image itself is never multisampled, since Bevy renders to a multisampled main
+            // texture and resolves it into the render target
+            msaa.copied().unwrap_or_default(),
+            PortalCamera(entity),
+        ));
+        if let Some(image_handle) = image_handle {
+            portal_camera.insert(PortalImage(image_handle.clone()));
+        }
+        if is_2d {
+            portal_camera.insert((
+                Camera2d,
+                orthographic_projection
+                    .cloned()
+                    .unwrap_or_else(OrthographicProjection::default_2d),
+            ));
+        } else {
+            let mut portal_projection = PortalProjection::new(
+                portal
+                    .projection
+                    .clone()
+                    .or_else(|| projection.cloned())
+                    .unwrap_or_default(),
+            );
+            if let Some(projection) = projection {
+                portal_projection.set_far(projection.far());
+            }
+            portal_camera.insert((camera_3d.cloned().unwrap_or_default(), portal_projection));
+            // `Camera3d` requires a `Projection`, but the `PortalProjection` should be the only
+            // projection driving the camera
+            portal_camera.remove::<Projection>();
+            if depth_prepass && inheritance.depth_prepass {
+                portal_camera.insert(DepthPrepass);
+            }
+            if normal_prepass && inheritance.normal_prepass {
+                portal_camera.insert(NormalPrepass);
+            }
+            if motion_vector_prepass && inheritance.motion_vector_prepass {
+                portal_camera.insert(MotionVectorPrepass);
+            }
+        }
+        if let Some(skybox) = skybox.filter(|_| inheritance.skybox) {
+            portal_camera.insert(skybox.clone());
+        }
+        if let Some(environment_map_light) =
+            environment_map_light.filter(|_| inheritance.environment_map)
+        {
+            portal_camera.insert(environment_map_light.clone());
+        }
+        if let Some(distance_fog) = distance_fog.filter(|_| inheritance.fog) {
+            portal_camera.insert(distance_fog.clone());
+        }
+        if recursion_depth > 0 {
+            let render_layers = portal.render_layers.clone().unwrap_or_default();
+            portal_camera.insert(render_layers.with(PORTAL_RECURSION_LAYER + level + 1));
+        } else if let Some(render_layers) = &portal.render_layers {
+            portal_camera.insert(render_layers.clone());
+        }
+        cameras.push(portal_camera.id());
+    }
+    portal.linked_camera = Some(cameras[0]);
+
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.insert((
+        PortalCulled::default(),
+        PortalView::default(),
+        PortalRenderedFrame::default(),
+        PendingPortalReady::default(),
+        LinkedPrimaryCamera {
+            camera: portal.primary_camera,
+            order: primary_camera.order,
+        },
+    ));
+    if let Some(image_handle) = &images[0] {
+        entity_commands.insert(PortalImage(image_handle.clone()));
+    }
+    if recursion_depth > 0 {
+        entity_commands.insert(PortalRecursion {
+            cameras: cameras[1..].to_vec(),
+            images: images[1..].iter().flatten().cloned().collect(),
+        });
+    }
+}
+
+/// Component on a [`Portal`] storing the [`Portal::primary_camera`] its [`PortalCamera`]s were
+/// set up for, along with that camera's [`Camera::order`], see [`relink_portal_cameras`].
+#[derive(Component, Debug)]
+struct LinkedPrimaryCamera {
+    camera: Entity,
+    order: isize,
+}
+
+/// Component on a [`Portal`] whose camera couldn't be set up, since no viewport size could be
+/// obtained for its [`Portal::primary_camera`].
+#[derive(Component, Debug)]
+struct PendingPortalSetup;
+
+/// Event triggered on a [`Portal`] entity to set up its camera again, see [`retry_portal_setup`].
+#[derive(Event, Debug)]
+struct RetryPortalSetup;
+
+/// System that retries the setup of [`Portal`]s marked with [`PendingPortalSetup`], once a
+/// viewport size can be obtained for their [`Portal::primary_camera`].
+///
+/// This lets portals be spawned before their window exists, e.g. in apps that start headless.
+fn retry_portal_setup(
+    mut commands: Commands,
+    portal_query: Query<(Entity, &Portal), With<PendingPortalSetup>>,
+    camera_query: Query<&Camera>,
+    portal_images: PortalImages,
+) {
+    for (entity, portal) in &portal_query {
+        let Ok(camera) = camera_query.get(portal.primary_camera) else {
+            continue;
+        };
+        if portal_images.size(camera, portal).is_none() {
+            continue;
+        }
+
+        commands.entity(entity).remove::<PendingPortalSetup>();
+        commands.trigger_targets(RetryPortalSetup, entity);
+    }
 }
 
-/// System that despawns a [`Portal::linked_camera`] when the [`Portal`] component is removed from
-/// a triggered entity.
+/// System that despawns a [`Portal::linked_camera`] (and any [`PortalRecursion::cameras`]) when
+/// the [`Portal`] component is removed from a triggered entity.
+///
+/// The [`PortalImage`] and [`PortalRecursion`] components are removed as well. Since the cameras
+/// hold the only strong handles to the images (besides the portal's materials), this allows the
+/// images to be freed, unless they are kept in the [`PortalImagePool`] for reuse.
 fn despawn_portal_camera(
     trigger: Trigger<OnRemove, Portal>,
-    portal_query: Query<&Portal>,
+    portal_query: Query<(&Portal, Option<&PortalImage>, Option<&PortalRecursion>)>,
     mut commands: Commands,
+    mut portal_images: PortalImages,
 ) {
-    let portal = portal_query.get(trigger.entity()).unwrap();
+    let entity = trigger.entity();
+    let (portal, portal_image, recursion) = portal_query.get(entity).unwrap();
+
+    // A user-provided render target isn't the portal's to reuse
+    let recursion_images = recursion.iter().flat_map(|recursion| &recursion.images);
+    for image_handle in portal_image
+        .filter(|_| portal.render_target.is_none())
+        .map(|image| &image.0)
+        .into_iter()
+        .chain(recursion_images)
+    {
+        portal_images.release(image_handle);
+    }
 
     if let Some(linked_camera) = portal.linked_camera {
         commands.entity(linked_camera).despawn_recursive();
     }
+
+    for &camera in recursion.iter().flat_map(|recursion| &recursion.cameras) {
+        commands.entity(camera).despawn_recursive();
+    }
+
+    commands.entity(entity).remove::<(
+        PortalImage,
+        PortalCulled,
+        PortalView,
+        PortalRenderedFrame,
+        PendingPortalReady,
+        PendingPortalSetup,
+        PortalRecursion,
+        LinkedPrimaryCamera,
+    )>();
 }
 
-/// System that updates a [`PortalCamera`]s [`Transform`] and [`GlobalTransform`] based on the
-/// primary camera.
-fn update_portal_camera_transform(
-    portal_query: Query<(&GlobalTransform, &Portal), (Without<Camera3d>, Without<PortalCamera>)>,
-    mut portal_camera_transform_query: Query<
-        (&mut GlobalTransform, &mut Transform),
+/// System that copies the [`Tonemapping`], [`DebandDither`], [`ColorGrading`], and [`Exposure`] of
+/// each [`Portal::primary_camera`] to its [`PortalCamera`]s whenever they change.
+///
+/// The exposure is offset by [`Portal::exposure_compensation`], so it's also updated when the
+/// [`Portal`] changes.
+fn sync_portal_camera_settings(
+    portal_query: Query<(Ref<Portal>, Option<&PortalRecursion>)>,
+    primary_camera_query: Query<
+        (
+            Option<Ref<Tonemapping>>,
+            Option<Ref<DebandDither>>,
+            Option<Ref<ColorGrading>>,
+            Option<Ref<Exposure>>,
+        ),
+        Without<PortalCamera>,
+    >,
+    mut camera_query: Query<
+        (
+            &mut Tonemapping,
+            &mut DebandDither,
+            &mut ColorGrading,
+            &mut Exposure,
+        ),
         With<PortalCamera>,
     >,
-    global_transform_query: Query<&GlobalTransform, Without<PortalCamera>>,
 ) {
-    for (portal_transform, portal) in &portal_query {
-        let Ok([primary_camera_transform, target_transform]) =
-            global_transform_query.get_many([portal.primary_camera, portal.target])
+    for (portal, recursion) in &portal_query {
+        let Ok((tonemapping, deband_dither, color_grading, exposure)) =
+            primary_camera_query.get(portal.primary_camera)
         else {
             continue;
         };
-
-        let Some((mut portal_camera_global_transform, mut portal_camera_transform)) = portal
-            .linked_camera
-            .and_then(|camera| portal_camera_transform_query.get_mut(camera).ok())
-        else {
+        let tonemapping = tonemapping.filter(DetectChanges::is_changed);
+        let deband_dither = deband_dither.filter(DetectChanges::is_changed);
+        let color_grading = color_grading.filter(DetectChanges::is_changed);
+        let exposure = (portal.is_changed() || exposure.as_ref().is_some_and(Ref::is_changed))
+            .then(|| portal_exposure(exposure.as_deref(), &portal));
+        if tonemapping.is_none()
+            && deband_dither.is_none()
+            && color_grading.is_none()
+            && exposure.is_none()
+        {
             continue;
-        };
-
-        // Transform the camera's translation from world space to the portal's space
-        let relative_translation = portal_transform
-            .affine()
-            .inverse()
-            .transform_point3(primary_camera_transform.translation());
-        // Now transform it back to world space using the target's transform
-        let translation = target_transform.transform_point(relative_translation);
+        }
 
-        let relative_rotation =
-            portal_transform.rotation().inverse() * primary_camera_transform.rotation();
-        let rotation = target_transform.rotation() * relative_rotation;
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
 
-        portal_camera_transform.translation = translation;
-        portal_camera_transform.rotation = rotation;
+        for camera in cameras {
+            let Ok((
+                mut camera_tonemapping,
+                mut camera_deband_dither,
+                mut camera_color_grading,
+                mut camera_exposure,
+            )) = camera_query.get_mut(camera)
+            else {
+                continue;
+            };
 
-        *portal_camera_global_transform = GlobalTransform::from(*portal_camera_transform);
+            if let Some(tonemapping) = &tonemapping {
+                *camera_tonemapping = **tonemapping;
+            }
+            if let Some(deband_dither) = &deband_dither {
+                *camera_deband_dither = **deband_dither;
+            }
+            if let Some(color_grading) = &color_grading {
+                *camera_color_grading = (*color_grading).clone();
+            }
+            if let Some(exposure) = exposure {
+                if camera_exposure.ev100 != exposure.ev100 {
+                    *camera_exposure = exposure;
+                }
+            }
+        }
     }
 }
 
-/// System that updates [`Frustum`] for [`PortalCamera`]s.
-fn update_portal_camera_frusta(
-    portal_query: Query<(&Portal, &GlobalTransform)>,
-    mut frustum_query: Query<&mut Frustum, With<PortalCamera>>,
-    global_transform_query: Query<&GlobalTransform>,
+/// System that updates the [`PortalCamera`]s of [`Portal`]s whose [`Portal::primary_camera`] was
+/// changed, e.g. when switching to a cutscene camera.
+///
+/// Rather than setting the portal up again, the existing cameras and images are kept, so the portal
+/// keeps showing its last frame in the meantime:
+/// * Each camera inherits the new primary camera's [`Camera`] settings (keeping its order
+///   relative to the primary camera), [`Tonemapping`], [`DebandDither`], [`ColorGrading`],
+///   [`Exposure`], [`Msaa`], and projection (only its far plane if [`Portal::projection`] is set).
+///   The components opted into by [`Portal::inheritance`] are inherited (or removed) too, except
+///   for prepasses.
+/// * Switching between a 2D and a 3D primary camera isn't supported, since the cameras would have
+///   to be set up again. A warning is logged, and the cameras are left as they are.
+/// * Each image is resized in place to the new primary camera's viewport (see
+///   [`PortalImages::size`]), and its format follows the new camera's HDR setting. If so,
+///   [`PortalImagesResized`] is sent.
+fn relink_portal_cameras(
+    mut commands: Commands,
+    mut images_resized_writer: EventWriter<PortalImagesResized>,
+    mut portal_query: Query<(
+        Entity,
+        &Portal,
+        &mut LinkedPrimaryCamera,
+        Option<&PortalImage>,
+        Option<&PortalRecursion>,
+    )>,
+    primary_camera_query: Query<
+        (
+            &Camera,
+            Option<&Projection>,
+            Option<&OrthographicProjection>,
+            Option<&Tonemapping>,
+            Option<&DebandDither>,
+            Option<&ColorGrading>,
+            Option<&Exposure>,
+            Option<&Msaa>,
+        ),
+        Without<PortalCamera>,
+    >,
+    inherited_query: Query<
+        (
+            Option<&Skybox>,
+            Option<&EnvironmentMapLight>,
+            Option<&DistanceFog>,
+            Has<Camera2d>,
+            Has<Camera3d>,
+        ),
+        Without<PortalCamera>,
+    >,
+    mut camera_query: Query<
+        (
+            &mut Camera,
+            Option<&mut PortalProjection>,
+            Option<&mut OrthographicProjection>,
+        ),
+        With<PortalCamera>,
+    >,
+    mut portal_images: PortalImages,
 ) {
-    for (portal, portal_transform) in &portal_query {
-        let Some(linked_camera) = portal.linked_camera else {
+    let mut is_resized = false;
+    for (entity, portal, mut linked, portal_image, recursion) in &mut portal_query {
+        if linked.camera == portal.primary_camera {
             continue;
-        };
+        }
+        let Ok((
+            primary_camera,
+            projection,
+            orthographic_projection,
+            tonemapping,
+            deband_dither,
+            color_grading,
+            exposure,
+            msaa,
+        )) = primary_camera_query.get(portal.primary_camera)
+        else {
+            continue;
+        };
+        let (skybox, environment_map_light, distance_fog, has_camera_2d, has_camera_3d) =
+            inherited_query
+                .get(portal.primary_camera)
+                .expect("primary camera should exist");
+        let inheritance = portal.inheritance;
 
-        let Ok(mut frustum) = frustum_query.get_mut(linked_camera) else {
+        // 2D portal cameras are set up differently, and can't be converted in place
+        let is_2d = has_camera_2d && !has_camera_3d;
+        let was_2d = portal
+            .linked_camera
+            .and_then(|camera| camera_query.get(camera).ok())
+            .is_some_and(|(_, portal_projection, _)| portal_projection.is_none());
+        if is_2d != was_2d {
+            warn!(
+                "could not relink the cameras of portal {entity} to primary camera {}: switching \
+                between 2D and 3D primary cameras isn't supported, respawn the portal instead",
+                portal.primary_camera
+            );
+            linked.camera = portal.primary_camera;
             continue;
+        }
+
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+        for camera_entity in cameras {
+            let Ok((mut camera, portal_projection, portal_orthographic_projection)) =
+                camera_query.get_mut(camera_entity)
+            else {
+                continue;
+            };
+            *camera = Camera {
+                order: camera.order - linked.order + primary_camera.order,
+                target: camera.target.clone(),
+                viewport: None,
+                is_active: camera.is_active,
+                clear_color: portal.clear_color.unwrap_or(primary_camera.clear_color),
+                ..primary_camera.clone()
+            };
+            if let (Some(mut portal_projection), Some(projection)) = (portal_projection, projection)
+            {
+                // The near plane and viewport size are kept, and updated as usual
+                if portal.projection.is_none() {
+                    portal_projection.projection = projection.clone();
+                }
+                portal_projection.set_far(projection.far());
+            }
+            if let (Some(mut portal_orthographic_projection), Some(orthographic_projection)) =
+                (portal_orthographic_projection, orthographic_projection)
+            {
+                *portal_orthographic_projection = orthographic_projection.clone();
+            }
+
+            let mut portal_camera = commands.entity(camera_entity);
+            portal_camera.insert((
+                tonemapping.copied().unwrap_or_default(),
+                deband_dither.copied().unwrap_or_default(),
+                color_grading.cloned().unwrap_or_default(),
+                portal_exposure(exposure, portal),
+                msaa.copied().unwrap_or_default(),
+            ));
+            match skybox.filter(|_| inheritance.skybox) {
+                Some(skybox) => portal_camera.insert(skybox.clone()),
+                None => portal_camera.remove::<Skybox>(),
+            };
+            match environment_map_light.filter(|_| inheritance.environment_map) {
+                Some(environment_map_light) => portal_camera.insert(environment_map_light.clone()),
+                None => portal_camera.remove::<EnvironmentMapLight>(),
+            };
+            match distance_fog.filter(|_| inheritance.fog) {
+                Some(distance_fog) => portal_camera.insert(distance_fog.clone()),
+                None => portal_camera.remove::<DistanceFog>(),
+            };
+        }
+
+        // A user-provided render target isn't the portal's to resize
+        if let Some(size) = portal_images
+            .size(primary_camera, portal)
+            .filter(|_| portal.render_target.is_none())
+        {
+            let format = image_format(primary_camera, portal);
+            let recursion_images = recursion.iter().flat_map(|recursion| &recursion.images);
+            for image_handle in portal_image
+                .map(|image| &image.0)
+                .into_iter()
+                .chain(recursion_images)
+            {
+                let needs_resize = portal_images.images.get(image_handle).is_some_and(|image| {
+                    image.texture_descriptor.size != size
+                        || image.texture_descriptor.format != format
+                });
+                if !needs_resize {
+                    continue;
+                }
+                let Some(image) = portal_images.images.get_mut(image_handle) else {
+                    continue;
+                };
+                if image.texture_descriptor.format != format {
+                    image.texture_descriptor.format = format;
+                    // Reallocated with the new format's pixel size when resized below
+                    image.data.clear();
+                }
+                image.resize(size);
+                is_resized = true;
+            }
+        }
+
+        *linked = LinkedPrimaryCamera {
+            camera: portal.primary_camera,
+            order: primary_camera.order,
         };
+    }
 
-        let Ok([primary_camera_transform, target_transform]) =
-            global_transform_query.get_many([portal.primary_camera, portal.target])
-        else {
+    if is_resized {
+        images_resized_writer.send(PortalImagesResized);
+    }
+}
+
+/// System that disables [`Portal`]s whose [`Portal::primary_camera`] has its [`Camera`] removed
+/// (e.g. by being despawned), logging a warning for each.
+///
+/// Without a primary camera, a portal's cameras can't be positioned. To use the portal again, set
+/// [`Portal::primary_camera`] to another camera and re-enable it.
+fn disable_orphaned_portals(
+    trigger: Trigger<OnRemove, Camera>,
+    mut portal_query: Query<(Entity, &mut Portal)>,
+) {
+    let camera = trigger.entity();
+    for (entity, mut portal) in &mut portal_query {
+        if portal.primary_camera != camera || !portal.enabled {
+            continue;
+        }
+
+        warn!("primary camera {camera} of portal {entity} was removed, disabling the portal");
+        portal.enabled = false;
+    }
+}
+
+/// System that stamps the [`PortalRenderedFrame`] of [`Portal`]s whose [`Portal::linked_camera`] is
+/// active this frame.
+fn update_portal_rendered_frames(
+    mut portal_query: Query<(&Portal, &mut PortalRenderedFrame)>,
+    camera_query: Query<&Camera, With<PortalCamera>>,
+    frame_count: Res<PortalFrameCount>,
+) {
+    for (portal, mut rendered_frame) in &mut portal_query {
+        let is_active = portal
+            .linked_camera
+            .and_then(|camera| camera_query.get(camera).ok())
+            .is_some_and(|camera| camera.is_active);
+        if is_active {
+            rendered_frame.0 = Some(frame_count.get());
+        }
+    }
+}
+
+/// System that triggers [`PortalReady`] on [`Portal`]s once their [`Portal::linked_camera`] has
+/// been active for [`PORTAL_READY_FRAMES`].
+fn trigger_portal_ready(
+    mut commands: Commands,
+    mut portal_query: Query<(Entity, &Portal, &mut PendingPortalReady)>,
+    camera_query: Query<&Camera, With<PortalCamera>>,
+) {
+    for (entity, portal, mut pending) in &mut portal_query {
+        let is_active = portal
+            .linked_camera
+            .and_then(|camera| camera_query.get(camera).ok())
+            .is_some_and(|camera| camera.is_active);
+        if !is_active {
+            continue;
+        }
+
+        pending.0 += 1;
+        if pending.0 >= PORTAL_READY_FRAMES {
+            commands.entity(entity).remove::<PendingPortalReady>();
+            commands.trigger_targets(PortalReady, entity);
+        }
+    }
+}
+
+/// System that updates the [`PortalCulled`] state of [`Portal`]s.
+///
+/// A portal is in view if its [`Aabb`] intersects the [`Frustum`] of [`Portal::primary_camera`],
+/// or of any active [`PortalCamera`] of another portal. The latter lags behind by a frame for
+/// portals seen through portals, since their cameras' activity is only updated afterwards.
+fn cull_portals(
+    mut portal_query: Query<(Entity, &Portal, &Aabb, &GlobalTransform, &mut PortalCulled)>,
+    frustum_query: Query<&Frustum, Without<PortalCamera>>,
+    portal_camera_query: Query<(&PortalCamera, &Camera, &Frustum)>,
+) {
+    for (entity, portal, aabb, transform, mut culled) in &mut portal_query {
+        let Ok(primary_frustum) = frustum_query.get(portal.primary_camera) else {
+            continue;
+        };
+
+        let aabb = if culled.out_of_view {
+            *aabb
+        } else {
+            Aabb {
+                center: aabb.center,
+                half_extents: aabb.half_extents + aabb.half_extents.max_element() * CULLING_MARGIN,
+            }
+        };
+        let world_from_local = transform.affine();
+        let portal_frusta = portal_camera_query
+            .iter()
+            .filter(|(portal_camera, camera, _)| portal_camera.0 != entity && camera.is_active)
+            .map(|(_, _, frustum)| frustum);
+
+        let visible = iter::once(primary_frustum)
+            .chain(portal_frusta)
+            .any(|frustum| frustum.intersects_obb(&aabb, &world_from_local, true, true));
+        if culled.out_of_view == visible {
+            culled.out_of_view = !visible;
+        }
+    }
+}
+
+/// System that updates [`PortalCulled::out_of_range`] based on the distance between a [`Portal`]
+/// and its [`Portal::primary_camera`].
+fn cull_distant_portals(
+    mut portal_query: Query<(&Portal, &GlobalTransform, &mut PortalCulled)>,
+    global_transform_query: Query<&GlobalTransform, Without<PortalCulled>>,
+) {
+    for (portal, transform, mut culled) in &mut portal_query {
+        let out_of_range = portal
+            .max_render_distance
+            .is_some_and(|max_render_distance| {
+                let Ok(primary_camera_transform) =
+                    global_transform_query.get(portal.primary_camera)
+                else {
+                    return false;
+                };
+                let max_render_distance = if culled.out_of_range {
+                    max_render_distance
+                } else {
+                    max_render_distance * (1.0 + CULLING_MARGIN)
+                };
+                transform
+                    .translation()
+                    .distance(primary_camera_transform.translation())
+                    > max_render_distance
+            });
+
+        if culled.out_of_range != out_of_range {
+            culled.out_of_range = out_of_range;
+        }
+    }
+}
+
+/// System that activates or deactivates [`PortalCamera`]s based on [`Portal::enabled`] and
+/// [`PortalCulled`].
+fn update_portal_camera_active(
+    portal_query: Query<
+        (&Portal, Option<&PortalCulled>, Option<&PortalRecursion>),
+        Or<(Changed<Portal>, Changed<PortalCulled>)>,
+    >,
+    mut camera_query: Query<&mut Camera, With<PortalCamera>>,
+) {
+    for (portal, culled, recursion) in &portal_query {
+        let is_active = portal.enabled && !culled.is_some_and(PortalCulled::is_culled);
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+
+        for camera in cameras {
+            let Ok(mut camera) = camera_query.get_mut(camera) else {
+                continue;
+            };
+
+            if camera.is_active != is_active {
+                camera.is_active = is_active;
+            }
+        }
+    }
+}
+
+/// System that deactivates the [`PortalCamera`]s of [`Portal`]s with a [`Portal::render_interval`]
+/// on the frames they aren't rendered, and activates them again on the frames they are, based on
+/// the [`PortalFrameCount`].
+///
+/// Runs after [`update_portal_camera_active`], and keeps disabled or culled portals deactivated.
+pub(crate) fn update_portal_camera_interval(
+    portal_query: Query<(
+        Entity,
+        &Portal,
+        Option<&PortalCulled>,
+        Option<&PortalRecursion>,
+    )>,
+    mut camera_query: Query<&mut Camera, With<PortalCamera>>,
+    frame_count: Res<PortalFrameCount>,
+) {
+    for (entity, portal, culled, recursion) in &portal_query {
+        if portal.render_interval <= 1 {
+            continue;
+        }
+        let phase = portal.render_phase.unwrap_or(entity.index());
+        let is_active = portal.enabled
+            && !culled.is_some_and(PortalCulled::is_culled)
+            && frame_count.get().wrapping_add(phase) % portal.render_interval == 0;
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+
+        for camera in cameras {
+            let Ok(mut camera) = camera_query.get_mut(camera) else {
+                continue;
+            };
+
+            if camera.is_active != is_active {
+                camera.is_active = is_active;
+            }
+        }
+    }
+}
+
+/// Marker component on the render world views of [`PortalCamera`]s whose [`Portal`] has
+/// [`Portal::render_shadows`] disabled.
+#[derive(Component)]
+struct NoPortalShadows;
+
+/// System that marks the render world views of [`PortalCamera`]s whose [`Portal`] has
+/// [`Portal::render_shadows`] disabled with [`NoPortalShadows`].
+fn extract_portal_shadows(
+    mut commands: Commands,
+    camera_query: Extract<Query<(RenderEntity, &Camera, &PortalCamera)>>,
+    portal_query: Extract<Query<&Portal>>,
+) {
+    for (render_entity, camera, portal_camera) in &camera_query {
+        if !camera.is_active {
+            continue;
+        }
+
+        let mut view = commands.entity(render_entity);
+        if portal_query
+            .get(portal_camera.0)
+            .is_ok_and(|portal| !portal.render_shadows)
+        {
+            view.insert(NoPortalShadows);
+        } else {
+            view.remove::<NoPortalShadows>();
+        }
+    }
+}
+
+/// System that empties the shadow phases of the [`DirectionalLight`] cascades of views with
+/// [`NoPortalShadows`], once they're queued.
+///
+/// The cascades' shadow maps are still cleared, so the light applies as if nothing cast a shadow.
+/// Point and spot light shadow maps are shared by all views, so they're left alone.
+fn clear_portal_shadow_phases(
+    view_query: Query<&ViewLightEntities, With<NoPortalShadows>>,
+    light_entity_query: Query<&LightEntity>,
+    shadow_phases: Option<ResMut<ViewBinnedRenderPhases<Shadow>>>,
+) {
+    let Some(mut shadow_phases) = shadow_phases else {
+        return;
+    };
+    for view_lights in &view_query {
+        for view_light in &view_lights.lights {
+            if !matches!(
+                light_entity_query.get(*view_light),
+                Ok(LightEntity::Directional { .. })
+            ) {
+                continue;
+            }
+            if let Some(shadow_phase) = shadow_phases.get_mut(view_light) {
+                shadow_phase.clear();
+            }
+        }
+    }
+}
+
+/// System that applies [`Portal::projection`] to the [`PortalProjection`] of a [`Portal`]'s
+/// [`PortalCamera`]s when the [`Portal`] changes.
+///
+/// The far plane of each [`PortalProjection`] is kept in sync with the [`Projection`] of
+/// [`Portal::primary_camera`], so that distant geometry is clipped at the same distance through the
+/// portal as outside of it.
+///
+/// For portals with a [`Portal::fixed_resolution`], [`PortalProjection::viewport_size`] is kept in
+/// sync with the primary camera's viewport, so that the view keeps its aspect ratio.
+fn update_portal_camera_projection(
+    portal_query: Query<(Ref<Portal>, Option<&PortalRecursion>)>,
+    primary_projection_query: Query<Ref<Projection>, Without<PortalCamera>>,
+    primary_camera_query: Query<&Camera, Without<PortalCamera>>,
+    mut portal_projection_query: Query<&mut PortalProjection, With<PortalCamera>>,
+) {
+    for (portal, recursion) in &portal_query {
+        let primary_projection = primary_projection_query.get(portal.primary_camera).ok();
+        let projection = portal.projection.as_ref().filter(|_| portal.is_changed());
+        let far = primary_projection
+            .filter(|primary_projection| primary_projection.is_changed() || projection.is_some())
+            .map(|primary_projection| primary_projection.far());
+        let viewport_size = portal.fixed_resolution.and_then(|_| {
+            primary_camera_query
+                .get(portal.primary_camera)
+                .ok()?
+                .logical_viewport_size()
+        });
+
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+
+        for camera in cameras {
+            let Ok(mut portal_projection) = portal_projection_query.get_mut(camera) else {
+                continue;
+            };
+            if let Some(projection) = projection {
+                portal_projection.projection = projection.clone();
+            }
+            if let Some(far) = far.filter(|&far| far != portal_projection.far()) {
+                portal_projection.set_far(far);
+            }
+            if portal_projection.viewport_size != viewport_size {
+                portal_projection.viewport_size = viewport_size;
+            }
+        }
+    }
+}
+
+/// System that updates a [`PortalCamera`]s [`Transform`] and [`GlobalTransform`] based on the
+/// primary camera.
+///
+/// Each [`PortalRecursion::cameras`] is positioned relative to the camera of the level before it,
+/// as if it were looking through the portal.
+///
+/// For portals with [`Portal::extrapolate_motion`] set, the primary camera's transform is first
+/// advanced by its movement since the previous frame.
+///
+/// The [`PortalView`] of each portal is updated to the transform of [`Portal::linked_camera`].
+fn update_portal_camera_transform(
+    mut portal_query: Query<
+        (
+            &GlobalTransform,
+            &Portal,
+            Option<&PortalRecursion>,
+            Option<&mut PortalView>,
+        ),
+        (Without<Camera3d>, Without<PortalCamera>),
+    >,
+    mut portal_camera_transform_query: Query<
+        (&mut GlobalTransform, &mut Transform),
+        With<PortalCamera>,
+    >,
+    global_transform_query: Query<&GlobalTransform, Without<PortalCamera>>,
+    mut previous_transforms: Local<HashMap<Entity, Transform>>,
+) {
+    // Only the primary cameras of portals extrapolating their motion are tracked, so that the
+    // transforms of cameras that are despawned or no longer used are dropped
+    let previous = mem::take(&mut *previous_transforms);
+
+    for (portal_transform, portal, recursion, mut view) in &mut portal_query {
+        let Ok(primary_camera_transform) = global_transform_query.get(portal.primary_camera) else {
+            continue;
+        };
+        let mut transform = primary_camera_transform.compute_transform();
+        if portal.extrapolate_motion {
+            previous_transforms.insert(portal.primary_camera, transform);
+            if let Some(previous) = previous.get(&portal.primary_camera) {
+                transform = extrapolate_transform(previous, &transform);
+            }
+        }
+
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+
+        let Some(linked_camera) = portal.linked_camera else {
+            continue;
+        };
+
+        for camera in iter::once(linked_camera).chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        ) {
+            let Ok((mut portal_camera_global_transform, mut portal_camera_transform)) =
+                portal_camera_transform_query.get_mut(camera)
+            else {
+                break;
+            };
+
+            transform = remap_transform(portal_transform, &target_transform, &transform);
+            if let Some(view) = view.as_mut().filter(|_| camera == linked_camera) {
+                view.set_if_neq(PortalView {
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                });
+            }
+
+            portal_camera_transform.translation = transform.translation;
+            portal_camera_transform.rotation = transform.rotation;
+
+            *portal_camera_global_transform = GlobalTransform::from(*portal_camera_transform);
+        }
+    }
+}
+
+/// System that keeps the [`Portal::target`] of portals with [`Portal::mirror_target`] set facing
+/// the opposite direction from the position of the portal.
+///
+/// Runs after transform propagation, so both the target's [`GlobalTransform`] and its [`Transform`]
+/// (relative to its parent, if any) are written.
+fn update_mirror_targets(
+    portal_query: Query<(Entity, &Portal)>,
+    parent_query: Query<&Parent>,
+    mut transform_params: ParamSet<(
+        Query<&GlobalTransform>,
+        Query<(&mut Transform, &mut GlobalTransform), Without<Portal>>,
+    )>,
+    mut mirror_targets: Local<Vec<(Entity, GlobalTransform, Option<GlobalTransform>)>>,
+) {
+    let global_transform_query = transform_params.p0();
+    for (entity, portal) in &portal_query {
+        if !portal.mirror_target || portal.target_transform.is_some() {
+            continue;
+        }
+        let Ok(portal_transform) = global_transform_query.get(entity) else {
+            continue;
+        };
+        // The parent is usually the portal itself
+        let parent_transform = parent_query
+            .get(portal.target)
+            .ok()
+            .and_then(|parent| global_transform_query.get(parent.get()).ok());
+        mirror_targets.push((
+            portal.target,
+            portal_transform.mul_transform(mirror_target_transform()),
+            parent_transform.copied(),
+        ));
+    }
+
+    let mut target_query = transform_params.p1();
+    for (target, mirrored_transform, parent_transform) in mirror_targets.drain(..) {
+        let Ok((mut transform, mut global_transform)) = target_query.get_mut(target) else {
+            continue;
+        };
+        if *global_transform == mirrored_transform {
+            continue;
+        }
+        *transform = match parent_transform {
+            Some(parent_transform) => mirrored_transform.reparented_to(&parent_transform),
+            None => mirrored_transform.compute_transform(),
+        };
+        *global_transform = mirrored_transform;
+    }
+}
+
+/// Returns the [`Transform`] of a mirror's target relative to the mirror, facing the opposite
+/// direction.
+fn mirror_target_transform() -> Transform {
+    Transform::from_rotation(Quat::from_rotation_y(PI))
+}
+
+/// Returns `current` advanced by the movement from `previous` to `current`, assuming it continues
+/// at the same rate.
+fn extrapolate_transform(previous: &Transform, current: &Transform) -> Transform {
+    let rotation = current.rotation * previous.rotation.inverse();
+    Transform {
+        translation: current.translation * 2.0 - previous.translation,
+        rotation: (rotation * current.rotation).normalize(),
+        scale: current.scale,
+    }
+}
+
+/// System that shifts the [`DistanceFog`] of [`PortalCamera`]s to be measured from
+/// [`Portal::target`], for portals using [`PortalFogMode::PhysicallyAccurate`].
+///
+/// The fog's start and end are those of [`Portal::primary_camera`]'s fog, pushed out by the
+/// distance between each camera and the target.
+fn update_portal_camera_fog(
+    portal_query: Query<(&Portal, Option<&PortalRecursion>)>,
+    primary_fog_query: Query<&DistanceFog, Without<PortalCamera>>,
+    mut portal_camera_query: Query<(&GlobalTransform, &mut DistanceFog), With<PortalCamera>>,
+    global_transform_query: Query<&GlobalTransform, Without<PortalCamera>>,
+) {
+    for (portal, recursion) in &portal_query {
+        let inheritance = portal.inheritance;
+        if !inheritance.fog || inheritance.fog_mode != PortalFogMode::PhysicallyAccurate {
+            continue;
+        }
+        let Ok(primary_fog) = primary_fog_query.get(portal.primary_camera) else {
+            continue;
+        };
+        let FogFalloff::Linear { start, end } = primary_fog.falloff else {
+            continue;
+        };
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+
+        for camera in cameras {
+            let Ok((camera_transform, mut fog)) = portal_camera_query.get_mut(camera) else {
+                continue;
+            };
+            let distance = camera_transform
+                .translation()
+                .distance(target_transform.translation());
+            let (start, end) = (start + distance, end + distance);
+            let is_shifted = matches!(
+                fog.falloff,
+                FogFalloff::Linear { start: fog_start, end: fog_end }
+                    if fog_start == start && fog_end == end
+            );
+            if !is_shifted {
+                fog.falloff = FogFalloff::Linear { start, end };
+            }
+        }
+    }
+}
+
+/// Remaps the translation and rotation of `transform` from the space of `portal_transform` to the
+/// space of `target_transform`.
+///
+/// Differences in scale between the portal and target are accounted for, so a target that is twice
+/// the size of its portal results in a view that is "bigger on the inside". Each scale is treated
+/// as uniform (see [`uniform_scale`]), since a non-uniform scale can't be represented by a
+/// [`Transform`]'s translation and rotation alone.
+pub(crate) fn remap_transform(
+    portal_transform: &GlobalTransform,
+    target_transform: &GlobalTransform,
+    transform: &Transform,
+) -> Transform {
+    let (portal_scale, portal_rotation, portal_translation) =
+        portal_transform.to_scale_rotation_translation();
+    let (target_scale, target_rotation, target_translation) =
+        target_transform.to_scale_rotation_translation();
+
+    // Transform the translation from world space to the portal's space
+    let relative_translation = portal_rotation.inverse()
+        * (transform.translation - portal_translation)
+        / uniform_scale(portal_scale);
+    // Now transform it back to world space using the target's transform
+    let translation =
+        target_translation + target_rotation * (relative_translation * uniform_scale(target_scale));
+
+    let relative_rotation = portal_rotation.inverse() * transform.rotation;
+    let rotation = target_rotation * relative_rotation;
+
+    Transform {
+        translation,
+        rotation,
+        ..*transform
+    }
+}
+
+/// Returns a single factor representing `scale`, being the geometric mean of its components.
+///
+/// A debug message is logged if `scale` is non-uniform, as the result is only an approximation.
+pub(crate) fn uniform_scale(scale: Vec3) -> f32 {
+    // Allow for some error, since the scale is usually decomposed from a `GlobalTransform`
+    if scale.max_element() - scale.min_element() > scale.abs().max_element() * 1e-4 {
+        debug!(
+            "portal or target has a non-uniform scale of {scale}, which can't be remapped exactly"
+        );
+    }
+    (scale.x * scale.y * scale.z).abs().cbrt()
+}
+
+/// System that updates the [`PortalProjection::near_plane`] of [`PortalCamera`]s to match the plane
+/// of [`Portal::target`] (offset by [`Portal::plane_offset`]).
+///
+/// The [`Frustum`] is then derived from the resulting projection, so that geometry behind the
+/// target is culled and clipped.
+///
+/// The near plane's distance from the camera is clamped to [`NEAR_PLANE_DISTANCE_RANGE`], logging
+/// a warning (at most every [`NEAR_PLANE_WARNING_INTERVAL`]) when it is. If the plane can't be
+/// determined at all, the projection's regular near plane is used instead.
+///
+/// [`Frustum`]: bevy::render::primitives::Frustum
+fn update_portal_camera_near_plane(
+    portal_query: Query<(
+        Entity,
+        &Portal,
+        &GlobalTransform,
+        Option<&Aabb>,
+        Option<&PortalRecursion>,
+    )>,
+    mut portal_camera_query: Query<(&GlobalTransform, &mut PortalProjection), With<PortalCamera>>,
+    global_transform_query: Query<&GlobalTransform, Without<PortalCamera>>,
+    time: Res<Time<Real>>,
+    mut last_warned: Local<Option<Duration>>,
+) {
+    for (entity, portal, portal_transform, aabb, recursion) in &portal_query {
+        let Some(linked_camera) = portal.linked_camera else {
             continue;
         };
 
-        let mut normal = target_transform.forward();
+        let Ok(primary_camera_transform) = global_transform_query.get(portal.primary_camera) else {
+            continue;
+        };
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
 
-        if portal.flip_near_plane_normal {
-            let camera_to_portal =
-                portal_transform.translation() - primary_camera_transform.translation();
-            if camera_to_portal.dot(*portal_transform.forward()) <= 0.0 {
-                normal = -normal;
+        let target_plane_transform = portal.plane_transform(&target_transform, aabb);
+        let mut normal = Portal::plane(&target_plane_transform).normal;
+
+        let flip = match portal.flip_near_plane_normal {
+            NearPlaneFlip::Fixed(flip) => flip,
+            NearPlaneFlip::Auto => {
+                let plane_transform = portal.plane_transform(portal_transform, aabb);
+                Portal::is_camera_on_back_side(&plane_transform, primary_camera_transform)
             }
+        };
+        if flip {
+            normal = -normal;
         }
 
-        let distance = -target_transform
-            .translation()
-            .dot(normal.normalize_or_zero());
-        frustum.half_spaces[4] = HalfSpace::new(normal.extend(distance));
+        let distance = -target_plane_transform.translation().dot(*normal);
+        let plane = normal.extend(distance);
+
+        // Every level of recursion looks through the target, so they all share the same plane
+        for camera in iter::once(linked_camera).chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        ) {
+            let Ok((portal_camera_transform, mut portal_projection)) =
+                portal_camera_query.get_mut(camera)
+            else {
+                continue;
+            };
+
+            // Planes transform by the inverse transpose, and we want to go from world space to
+            // view space, so we use the transpose of `world_from_view`
+            let near_plane = portal_camera_transform.compute_matrix().transpose() * plane;
+            let (near_plane, clamped) = clamp_near_plane(near_plane, portal_projection.far());
+
+            let now = time.elapsed();
+            if clamped && last_warned.is_none_or(|last| now - last >= NEAR_PLANE_WARNING_INTERVAL) {
+                warn!(
+                    "near plane of portal camera {camera} (of portal {entity}) was clamped, since \
+                    it was too close to the camera or the far plane"
+                );
+                *last_warned = Some(now);
+            }
+
+            if portal_projection.near_plane != near_plane {
+                portal_projection.near_plane = near_plane;
+            }
+        }
     }
 }
 
-/// System that resizes [`PortalImage`]s when the [`WindowResized`] event is fired.
+/// Normalizes a view space `near_plane`, clamping its distance from the camera to
+/// [`NEAR_PLANE_DISTANCE_RANGE`] relative to `far`.
+///
+/// Returns the plane (or `None` if it's degenerate), and whether it was clamped. Planes the camera
+/// is on the visible side of are left as is, since they're not used as a near plane anyway.
+fn clamp_near_plane(near_plane: Vec4, far: f32) -> (Option<Vec4>, bool) {
+    let length = near_plane.truncate().length();
+    if !near_plane.is_finite() || length <= f32::EPSILON {
+        return (None, true);
+    }
+    let near_plane = near_plane / length;
+    // The camera is on the visible side of the plane
+    if near_plane.w > 0.0 {
+        return (Some(near_plane), false);
+    }
+
+    let (min, max) = NEAR_PLANE_DISTANCE_RANGE;
+    let distance = -near_plane.w;
+    let clamped_distance = distance.clamp(min * far, max * far);
+    (
+        Some(near_plane.truncate().extend(-clamped_distance)),
+        clamped_distance != distance,
+    )
+}
+
+/// System that resizes [`PortalImage`]s when the [`WindowResized`] event is fired, or when the
+/// [`Camera::viewport`] of a [`Portal::primary_camera`] changes size.
+///
+/// Changes are accumulated until none have been received for
+/// [`PortalCameraPlugin::resize_debounce`], after which each image is resized once to the final
+/// size of its camera's viewport (or window), and [`PortalImagesResized`] is sent.
+///
+/// Only portals whose [`Portal::primary_camera`] renders to a resized window, or has a resized
+/// viewport, are affected. Portals with a [`Portal::fixed_resolution`] are ignored. Images are
+/// sized the same way they're created, see [`PortalImages::size`].
+///
+/// Portal images are added to [`Assets<Image>`] as soon as they're created, so an image missing
+/// from it was removed while in use (e.g. by asset hot-reloading), rather than still loading. Such
+/// an image is re-created if possible (see [`PortalImages::restore`]), and a warning is logged (at
+/// most every [`MISSING_IMAGE_WARNING_INTERVAL`]).
 fn resize_portal_images(
     mut resized_reader: EventReader<WindowResized>,
-    window_query: Query<&Window>,
-    portal_image_query: Query<&PortalImage>,
-    mut images: ResMut<Assets<Image>>,
+    mut images_resized_writer: EventWriter<PortalImagesResized>,
+    mut pending: ResMut<PendingPortalResize>,
+    time: Res<Time<Real>>,
+    primary_window_query: Query<Entity, With<PrimaryWindow>>,
+    portal_query: Query<(Entity, &Portal, &PortalImage, Option<&PortalRecursion>)>,
+    camera_query: Query<&Camera>,
+    mut portal_images: PortalImages,
+    mut last_warned: Local<Option<Duration>>,
 ) {
-    for event in resized_reader.read() {
-        let window_size = window_query.get(event.window).unwrap().physical_size();
-        let size = Extent3d {
-            width: window_size.x,
-            height: window_size.y,
-            ..default()
+    let now = time.elapsed();
+    if !resized_reader.is_empty() {
+        pending
+            .windows
+            .extend(resized_reader.read().map(|event| event.window));
+        pending.last_resized = now;
+    }
+
+    // Viewports aren't tied to window events (e.g. split-screen viewports are set by the user), so
+    // they're compared against the size of their portals' images instead
+    for (_, portal, portal_image, _) in &portal_query {
+        if portal.render_target.is_some() {
+            continue;
+        }
+        let Ok(camera) = camera_query.get(portal.primary_camera) else {
+            continue;
         };
+        let Some(viewport_size) = camera
+            .viewport
+            .as_ref()
+            .map(|viewport| viewport.physical_size)
+        else {
+            continue;
+        };
+        // A missing image is treated as resized, so that it's re-created
+        let image_size = portal_images.images.get(&portal_image.0).map(Image::size);
+        let is_resized = portal_images
+            .size(camera, portal)
+            .is_some_and(|size| Some(UVec2::new(size.width, size.height)) != image_size);
+        if is_resized
+            && pending
+                .viewports
+                .insert(portal.primary_camera, viewport_size)
+                != Some(viewport_size)
+        {
+            pending.last_resized = now;
+        }
+    }
 
-        for portal_image in &portal_image_query {
-            let Some(image) = images.get_mut(&portal_image.0) else {
+    if (pending.windows.is_empty() && pending.viewports.is_empty())
+        || now.saturating_sub(pending.last_resized) < pending.debounce
+    {
+        return;
+    }
+    let resized_windows = mem::take(&mut pending.windows);
+    let resized_viewports = mem::take(&mut pending.viewports);
+    images_resized_writer.send(PortalImagesResized);
+
+    let primary_window = primary_window_query.get_single().ok();
+
+    for (entity, portal, portal_image, recursion) in &portal_query {
+        // A fixed resolution (or a user-provided render target) isn't affected by the size of the
+        // window
+        if portal.fixed_resolution.is_some() || portal.render_target.is_some() {
+            continue;
+        }
+
+        let Ok(camera) = camera_query.get(portal.primary_camera) else {
+            continue;
+        };
+
+        // A viewport is only affected by the size of its window if it was changed along with it
+        let is_resized = if camera.viewport.is_some() {
+            resized_viewports.contains_key(&portal.primary_camera)
+        } else {
+            matches!(
+                camera.target.normalize(primary_window),
+                Some(NormalizedRenderTarget::Window(window_ref))
+                    if resized_windows.contains(&window_ref.entity())
+            )
+        };
+        if !is_resized {
+            continue;
+        }
+
+        let Some(size) = portal_images.size(camera, portal) else {
+            continue;
+        };
+        let recursion_images = recursion.iter().flat_map(|recursion| &recursion.images);
+        let cameras = portal.linked_camera.into_iter().chain(
+            recursion
+                .iter()
+                .flat_map(|recursion| recursion.cameras.iter().copied()),
+        );
+
+        for image_handle in iter::once(&portal_image.0).chain(recursion_images) {
+            let Some(image) = portal_images.images.get_mut(image_handle) else {
+                let format = image_format(camera, portal);
+                let is_restored = portal_images.restore(
+                    image_handle,
+                    size,
+                    format,
+                    camera_query.iter_many(cameras.clone()),
+                );
+                if last_warned.is_none_or(|last| now - last >= MISSING_IMAGE_WARNING_INTERVAL) {
+                    if is_restored {
+                        warn!(
+                            "image of portal {entity} was removed from Assets<Image> while in \
+                            use, re-creating it"
+                        );
+                    } else {
+                        warn!(
+                            "image of portal {entity} was removed from Assets<Image>, and no \
+                            portal camera renders to it anymore; the portal will stay blank"
+                        );
+                    }
+                    *last_warned = Some(now);
+                }
                 continue;
             };
 
@@ -260,60 +2159,187 @@ fn resize_portal_images(
     }
 }
 
+/// System that resizes [`PortalImage`]s when the resolution of their [`Portal`] is changed, i.e.
+/// [`Portal::resolution_scale`] or [`Portal::fixed_resolution`], e.g. by a quality setting.
+///
+/// Unlike window resizes, this isn't debounced. Images are resized in place, so their handles (and
+/// therefore the portal's material) stay valid, and [`PortalImagesResized`] is sent.
+fn resize_changed_portal_images(
+    mut images_resized_writer: EventWriter<PortalImagesResized>,
+    portal_query: Query<(&Portal, &PortalImage, Option<&PortalRecursion>), Changed<Portal>>,
+    camera_query: Query<&Camera>,
+    mut portal_images: PortalImages,
+) {
+    let mut is_resized = false;
+    for (portal, portal_image, recursion) in &portal_query {
+        if portal.render_target.is_some() {
+            continue;
+        }
+        let Some(size) = camera_query
+            .get(portal.primary_camera)
+            .ok()
+            .and_then(|camera| portal_images.size(camera, portal))
+        else {
+            continue;
+        };
+        let recursion_images = recursion.iter().flat_map(|recursion| &recursion.images);
+
+        for image_handle in iter::once(&portal_image.0).chain(recursion_images) {
+            // Only borrow the image mutably if it needs resizing, since that marks it as modified
+            let needs_resize = portal_images
+                .images
+                .get(image_handle)
+                .is_some_and(|image| image.texture_descriptor.size != size);
+            if !needs_resize {
+                continue;
+            }
+            if let Some(image) = portal_images.images.get_mut(image_handle) {
+                image.resize(size);
+                is_resized = true;
+            }
+        }
+    }
+
+    if is_resized {
+        images_resized_writer.send(PortalImagesResized);
+    }
+}
+
+/// Returns the [`Exposure`] of the [`PortalCamera`]s of `portal`, being the `exposure` of its
+/// primary camera offset by [`Portal::exposure_compensation`].
+pub(crate) fn portal_exposure(exposure: Option<&Exposure>, portal: &Portal) -> Exposure {
+    let exposure = exposure.copied().unwrap_or_default();
+    Exposure {
+        ev100: exposure.ev100 - portal.exposure_compensation,
+    }
+}
+
+/// Returns the format of the image of `portal` rendered for the given `camera`, being
+/// [`Portal::texture_format`] if set, otherwise based on whether `camera` has HDR enabled.
+fn image_format(camera: &Camera, portal: &Portal) -> TextureFormat {
+    portal.texture_format.unwrap_or(if camera.hdr {
+        ViewTarget::TEXTURE_FORMAT_HDR
+    } else {
+        TextureFormat::Bgra8UnormSrgb
+    })
+}
+
+/// Scales the width and height of `size` by `scale`, keeping them at a minimum of 1 pixel.
+fn scale_size(size: Extent3d, scale: f32) -> Extent3d {
+    let scale = |value: u32| ((value as f32 * scale).round() as u32).max(1);
+    Extent3d {
+        width: scale(size.width),
+        height: scale(size.height),
+        ..size
+    }
+}
+
 #[derive(SystemParam)]
 struct PortalImages<'w, 's> {
     primary_window_query: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
     window_query: Query<'w, 's, &'static Window>,
     images: ResMut<'w, Assets<Image>>,
     manual_texture_views: Res<'w, ManualTextureViews>,
+    pool: ResMut<'w, PortalImagePool>,
 }
 
 impl PortalImages<'_, '_> {
-    /// Creates a new [`Image`] with size matching the given `camera`.
+    /// Creates a new [`Image`] for `portal`, sized for the given `camera` (see
+    /// [`PortalImages::size`]).
+    ///
+    /// The image's format is [`Portal::texture_format`] if set, otherwise it's based on whether
+    /// `camera` has HDR enabled. The image is filled with [`Portal::placeholder_color`] if set.
+    ///
+    /// If the [`PortalImagePool`] contains an image of the same size and format, it is reused
+    /// instead. It is then only refilled if [`Portal::placeholder_color`] is set, since any
+    /// modification causes the image to be re-uploaded.
     ///
     /// Returns `None` if no viewport size could be obtained.
-    fn new(&mut self, camera: &Camera) -> Option<Handle<Image>> {
-        let size = self.get_viewport_size(camera)?;
-        let format = TextureFormat::Bgra8UnormSrgb;
-        let image = Image {
-            data: vec![0; size.volume() * format.pixel_size()],
-            texture_descriptor: TextureDescriptor {
-                label: None,
-                size,
-                dimension: TextureDimension::D2,
-                format,
-                mip_level_count: 1,
-                sample_count: 1,
-                usage: TextureUsages::TEXTURE_BINDING
-                    | TextureUsages::COPY_DST
-                    | TextureUsages::RENDER_ATTACHMENT,
-                view_formats: &[],
-            },
-            ..default()
-        };
+    fn create(&mut self, camera: &Camera, portal: &Portal) -> Option<Handle<Image>> {
+        let size = self.size(camera, portal)?;
+        let format = image_format(camera, portal);
+        if let Some(handle) = self.pool.take(size, format) {
+            if let Some(placeholder_color) = portal.placeholder_color {
+                if let Some(image) = self.images.get_mut(&handle) {
+                    fill_image(image, placeholder_color);
+                }
+            }
+            return Some(handle);
+        }
+
+        let mut image = new_image(size, format);
+        if let Some(placeholder_color) = portal.placeholder_color {
+            fill_image(&mut image, placeholder_color);
+        }
         let handle = self.images.add(image);
         Some(handle)
     }
 
-    /// Retrieves the size of the viewport of a given `camera`.
+    /// Re-creates the portal image behind `handle` with the given `size` and `format`, if it was
+    /// removed from [`Assets<Image>`] while one of the given `cameras` still renders to it, e.g. by
+    /// asset hot-reloading.
     ///
-    /// Returns `None` if no sizing could be obtained.
-    fn get_viewport_size(&self, camera: &Camera) -> Option<Extent3d> {
-        match camera.viewport.as_ref() {
-            Some(viewport) => Some(viewport.physical_size),
-            None => match &camera.target {
-                RenderTarget::Window(window_ref) => (match window_ref {
-                    WindowRef::Primary => self.primary_window_query.get_single().ok(),
-                    WindowRef::Entity(entity) => self.window_query.get(*entity).ok(),
-                })
-                .map(Window::physical_size),
-                RenderTarget::Image(handle) => self.images.get(handle).map(Image::size),
-                RenderTarget::TextureView(handle) => self
-                    .manual_texture_views
-                    .get(handle)
-                    .map(|texture| texture.size),
-            },
+    /// The image is inserted under the same handle, so the cameras and materials using it stay
+    /// valid. Returns `false` if none of the `cameras` hold the image, meaning the handle is
+    /// dangling and can't be restored.
+    fn restore<'a>(
+        &mut self,
+        handle: &Handle<Image>,
+        size: Extent3d,
+        format: TextureFormat,
+        mut cameras: impl Iterator<Item = &'a Camera>,
+    ) -> bool {
+        // A strong handle keeps the asset's slot from being reused, so inserting into it is safe
+        let is_held = cameras.any(|camera| {
+            matches!(
+                &camera.target,
+                RenderTarget::Image(target) if target.is_strong() && target.id() == handle.id()
+            )
+        });
+        if is_held {
+            self.images.insert(handle.id(), new_image(size, format));
         }
+        is_held
+    }
+
+    /// Adds the image of a despawned portal to the [`PortalImagePool`], if it still exists.
+    fn release(&mut self, handle: &Handle<Image>) {
+        let Some(image) = self.images.get(handle) else {
+            return;
+        };
+        let (size, format) = (
+            image.texture_descriptor.size,
+            image.texture_descriptor.format,
+        );
+        if let Some(handle) = self.images.get_strong_handle(handle.id()) {
+            self.pool.release(size, format, handle);
+        }
+    }
+
+    /// Returns the size of the image of `portal`, being the size of the viewport of the given
+    /// `camera` scaled by [`Portal::resolution_scale`], or [`Portal::fixed_resolution`] if set.
+    ///
+    /// Returns `None` if no viewport size could be obtained.
+    fn size(&self, camera: &Camera, portal: &Portal) -> Option<Extent3d> {
+        Some(match portal.fixed_resolution {
+            Some(fixed_resolution) => Extent3d {
+                width: fixed_resolution.x.max(1),
+                height: fixed_resolution.y.max(1),
+                ..default()
+            },
+            None => scale_size(self.get_viewport_size(camera)?, portal.resolution_scale),
+        })
+    }
+
+    /// Retrieves the size of the viewport of a given `camera`, see [`viewport_size`].
+    fn get_viewport_size(&self, camera: &Camera) -> Option<Extent3d> {
+        viewport_size(
+            camera,
+            self.primary_window_query.get_single().ok(),
+            &self.window_query,
+            &self.images,
+            &self.manual_texture_views,
+        )
         .map(|size| Extent3d {
             width: size.x,
             height: size.y,
@@ -321,3 +2347,72 @@ impl PortalImages<'_, '_> {
         })
     }
 }
+
+/// Returns the physical size of the viewport of the given `camera`, being the size of its
+/// [`Camera::viewport`] if set, otherwise the size of its render target.
+///
+/// This is the size a [`Portal`] viewed by `camera` renders at, before [`Portal::resolution_scale`]
+/// is applied (unless [`Portal::fixed_resolution`] is set). `primary_window` is used for
+/// [`WindowRef::Primary`] targets, and `window_query` for other windows.
+///
+/// Returns `None` if the render target doesn't exist (yet).
+pub fn viewport_size(
+    camera: &Camera,
+    primary_window: Option<&Window>,
+    window_query: &Query<&Window>,
+    images: &Assets<Image>,
+    manual_texture_views: &ManualTextureViews,
+) -> Option<UVec2> {
+    if let Some(viewport) = camera.viewport.as_ref() {
+        return Some(viewport.physical_size);
+    }
+    match &camera.target {
+        RenderTarget::Window(WindowRef::Primary) => primary_window.map(Window::physical_size),
+        RenderTarget::Window(WindowRef::Entity(entity)) => {
+            window_query.get(*entity).ok().map(Window::physical_size)
+        }
+        RenderTarget::Image(handle) => images.get(handle).map(Image::size),
+        RenderTarget::TextureView(handle) => {
+            manual_texture_views.get(handle).map(|texture| texture.size)
+        }
+    }
+}
+
+/// Returns a blank portal image of the given `size` and `format`, that can be rendered to.
+fn new_image(size: Extent3d, format: TextureFormat) -> Image {
+    Image {
+        data: vec![0; size.volume() * format.pixel_size()],
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format,
+            mip_level_count: 1,
+            sample_count: 1,
+            // `COPY_SRC` allows the image to be read back, see `crate::snapshot`
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    }
+}
+
+/// Sets every pixel of `image` to `color`.
+pub(crate) fn fill_image(image: &mut Image, color: Color) {
+    let format = image.texture_descriptor.format;
+    let mut pixel = Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &vec![0; format.pixel_size()],
+        format,
+        RenderAssetUsages::default(),
+    );
+    if pixel.set_color_at(0, 0, color).is_err() {
+        return;
+    }
+
+    image.data = pixel.data.repeat(image.texture_descriptor.size.volume());
+}
@@ -0,0 +1,446 @@
+//! Rendering [`Portal`]s through a cubemap, for portals that are looked through from many angles.
+//!
+//! A regular portal renders a single image from a [`PortalCamera`] placed behind its target, which
+//! is sampled in screen space. Inserting a [`PortalCubemap`] next to a [`Portal`] instead renders
+//! the view around the target into the faces of a small cubemap, sampled by the direction from the
+//! viewer through the portal's surface. The view through the portal doesn't depend on the size of
+//! the primary camera's viewport, and holds up when the portal is looked at from extreme angles,
+//! at the cost of rendering up to five views per portal.
+//!
+//! The cubemap is captured from the target's position, so the view through the portal has no
+//! parallax: geometry close to the target appears as if it were far away. This works best for
+//! portals into large or distant spaces, such as a window onto a skybox or landscape.
+//!
+//! Cubemap portals are only supported in 3D, and don't support [`Portal::recursion_depth`].
+//!
+//! [`PortalCamera`]: crate::camera::PortalCamera
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    asset::load_internal_asset,
+    core_pipeline::tonemapping::{DebandDither, Tonemapping},
+    image::TextureFormatPixelInfo,
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::{
+        camera::{CameraProjection, Exposure, RenderTarget},
+        mesh::MeshVertexBufferLayoutRef,
+        render_resource::{
+            AsBindGroup, Extent3d, Face, RenderPipelineDescriptor, ShaderRef,
+            SpecializedMeshPipelineError, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::{ColorGrading, ViewTarget},
+    },
+};
+
+use crate::{
+    camera::{
+        fill_image, portal_exposure, update_portal_camera_interval, PortalCameraOrder,
+        PortalCameraSystems, PortalCulled,
+    },
+    material::{PortalMaterial, PortalTextureSinks},
+    Portal,
+};
+
+pub const PORTAL_CUBEMAP_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(80419244516097461257834391645298815307);
+
+/// The direction each face of a cubemap looks in and its up direction, relative to the target.
+///
+/// The face looking out of the back of the target (along its local `+Z`) is never seen through the
+/// portal, so it isn't rendered. This must match the faces sampled by `portal_cubemap.wgsl`.
+const FACES: [(Vec3, Vec3); 5] = [
+    (Vec3::NEG_Z, Vec3::Y),
+    (Vec3::X, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Y),
+    (Vec3::Y, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::NEG_Z),
+];
+
+/// Plugin that renders [`Portal`]s with a [`PortalCubemap`] through a cubemap.
+pub struct PortalCubemapPlugin;
+
+impl Plugin for PortalCubemapPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PORTAL_CUBEMAP_SHADER_HANDLE,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/assets/portal_cubemap.wgsl"),
+            Shader::from_wgsl
+        );
+
+        // Portals with a `PortalCubemap` get a `PortalCubemapMaterial` rather than a
+        // `PortalMaterial`
+        let component_id = app.world_mut().register_component::<PortalCubemap>();
+        app.init_resource::<PortalTextureSinks>();
+        app.world_mut()
+            .resource_mut::<PortalTextureSinks>()
+            .0
+            .push(component_id);
+
+        app.add_plugins(MaterialPlugin::<PortalCubemapMaterial>::default())
+            .add_systems(
+                PostUpdate,
+                (
+                    update_cubemap_cameras_active.after(update_portal_camera_interval),
+                    update_cubemap_camera_transforms.in_set(PortalCameraSystems::UpdateTransform),
+                    update_cubemap_materials,
+                ),
+            )
+            .add_observer(setup_cubemap)
+            .add_observer(despawn_cubemap)
+            .register_type::<(PortalCubemap, PortalCubemapCamera)>();
+    }
+}
+
+/// Component that makes a [`Portal`] render through a cubemap, see the [module docs](self).
+///
+/// Insert this along with the [`Portal`]. The portal's mesh gets a [`PortalCubemapMaterial`] in
+/// place of a [`PortalMaterial`], and its [`Portal::linked_camera`] is kept inactive.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component, Debug)]
+pub struct PortalCubemap {
+    /// The width and height of each face of the cubemap, in pixels.
+    ///
+    /// Defaults to `512`.
+    pub resolution: u32,
+}
+
+impl Default for PortalCubemap {
+    fn default() -> Self {
+        Self { resolution: 512 }
+    }
+}
+
+impl PortalCubemap {
+    /// Creates a [`PortalCubemap`] with faces of the given `resolution`.
+    #[inline]
+    #[must_use]
+    pub fn new(resolution: u32) -> Self {
+        Self { resolution }
+    }
+}
+
+/// Component used to mark the cameras rendering the faces of a [`PortalCubemap`].
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component, Debug)]
+pub struct PortalCubemapCamera {
+    /// The entity containing the [`Portal`].
+    pub portal: Entity,
+    /// The index of the face rendered by this camera, in the order of the fields of
+    /// [`PortalCubemapMaterial`].
+    pub face: usize,
+}
+
+/// Component that stores the cameras rendering the faces of a [`PortalCubemap`].
+#[derive(Component, Debug)]
+pub struct PortalCubemapCameras(pub [Entity; FACES.len()]);
+
+/// Material used for the mesh of a [`Portal`] with a [`PortalCubemap`].
+///
+/// Each fragment samples the face of the cubemap in the direction from the viewer through the
+/// portal's surface.
+#[derive(Asset, AsBindGroup, Clone, Reflect, Debug)]
+#[bind_group_data(PortalCubemapMaterialKey)]
+pub struct PortalCubemapMaterial {
+    /// The inverse rotation of the portal, turning world space directions into the portal's local
+    /// space.
+    ///
+    /// This is kept in sync with the portal's [`GlobalTransform`].
+    #[uniform(0)]
+    pub portal_from_world: Mat3,
+    /// The face of the cubemap looking through the target, along its local `-Z`.
+    #[texture(1)]
+    #[sampler(6)]
+    pub forward: Handle<Image>,
+    /// The face of the cubemap looking along the target's local `+X`.
+    #[texture(2)]
+    pub right: Handle<Image>,
+    /// The face of the cubemap looking along the target's local `-X`.
+    #[texture(3)]
+    pub left: Handle<Image>,
+    /// The face of the cubemap looking along the target's local `+Y`.
+    #[texture(4)]
+    pub up: Handle<Image>,
+    /// The face of the cubemap looking along the target's local `-Y`.
+    #[texture(5)]
+    pub down: Handle<Image>,
+    /// Specifies which side of the portal to cull, see [`PortalMaterial::cull_mode`].
+    ///
+    /// This field's value is initialized from [`Portal::cull_mode`].
+    #[reflect(remote = crate::reflect::OptionFaceRemote)]
+    pub cull_mode: Option<Face>,
+}
+
+impl Material for PortalCubemapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        PORTAL_CUBEMAP_SHADER_HANDLE.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        _layout: &MeshVertexBufferLayoutRef,
+        key: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        descriptor.primitive.cull_mode = key.bind_group_data.cull_mode;
+        Ok(())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct PortalCubemapMaterialKey {
+    cull_mode: Option<Face>,
+}
+
+impl From<&PortalCubemapMaterial> for PortalCubemapMaterialKey {
+    fn from(material: &PortalCubemapMaterial) -> Self {
+        Self {
+            cull_mode: material.cull_mode,
+        }
+    }
+}
+
+/// Creates a square image for a face of a [`PortalCubemap`].
+fn create_face_image(resolution: u32, format: TextureFormat, portal: &Portal) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: resolution.max(1),
+            height: resolution.max(1),
+            ..default()
+        },
+        TextureDimension::D2,
+        &vec![0; format.pixel_size()],
+        format,
+        default(),
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    if let Some(placeholder_color) = portal.placeholder_color {
+        fill_image(&mut image, placeholder_color);
+    }
+    image
+}
+
+/// System that spawns the cameras and images of a [`PortalCubemap`], and inserts its
+/// [`PortalCubemapMaterial`].
+///
+/// The cameras inherit their settings from the [`Portal::primary_camera`] like a [`Portal`]'s own
+/// camera does, but always render with a perspective projection with a 90 degree field of view.
+fn setup_cubemap(
+    trigger: Trigger<OnAdd, PortalCubemap>,
+    mut commands: Commands,
+    portal_query: Query<(&Portal, &PortalCubemap, &GlobalTransform)>,
+    primary_camera_query: Query<(
+        &Camera,
+        Has<Camera2d>,
+        Option<&Projection>,
+        Option<&Tonemapping>,
+        Option<&DebandDither>,
+        Option<&ColorGrading>,
+        Option<&Exposure>,
+        Option<&Msaa>,
+    )>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<PortalCubemapMaterial>>,
+    mut camera_order: ResMut<PortalCameraOrder>,
+) {
+    let entity = trigger.entity();
+    let Ok((portal, cubemap, portal_transform)) = portal_query.get(entity) else {
+        warn!("PortalCubemap on {entity} has no effect, since the entity has no Portal");
+        return;
+    };
+    let Ok((
+        primary_camera,
+        is_2d,
+        projection,
+        tonemapping,
+        deband_dither,
+        color_grading,
+        exposure,
+        msaa,
+    )) = primary_camera_query.get(portal.primary_camera)
+    else {
+        error!(
+            "could not setup portal cubemap {entity}: primary_camera does not contain a Camera \
+            component"
+        );
+        return;
+    };
+    if is_2d {
+        warn!("portal {entity} has a PortalCubemap, but cubemaps aren't supported in 2D");
+        return;
+    }
+    if portal.recursion_depth > 0 {
+        warn!("portal {entity} has a recursion_depth, but recursion isn't supported by cubemaps");
+    }
+
+    let format = portal.texture_format.unwrap_or(if primary_camera.hdr {
+        ViewTarget::TEXTURE_FORMAT_HDR
+    } else {
+        TextureFormat::Bgra8UnormSrgb
+    });
+    let face_images =
+        FACES.map(|_| images.add(create_face_image(cubemap.resolution, format, portal)));
+
+    // The faces don't depend on each other, so they can share an order in a range of their own
+    let order = primary_camera.order - 1 - camera_order.0;
+    camera_order.0 += 1;
+
+    let far = projection.map_or(PerspectiveProjection::default().far, Projection::far);
+    let mut face = 0;
+    let cameras = face_images.clone().map(|image| {
+        let mut camera = commands.spawn((
+            Name::new(format!("Portal Cubemap Camera ({entity})")),
+            Camera3d::default(),
+            Camera {
+                order,
+                target: RenderTarget::Image(image),
+                viewport: None,
+                clear_color: portal.clear_color.unwrap_or(primary_camera.clear_color),
+                ..primary_camera.clone()
+            },
+            Projection::Perspective(PerspectiveProjection {
+                fov: FRAC_PI_2,
+                aspect_ratio: 1.0,
+                far,
+                ..default()
+            }),
+            tonemapping.copied().unwrap_or_default(),
+            deband_dither.copied().unwrap_or_default(),
+            color_grading.cloned().unwrap_or_default(),
+            portal_exposure(exposure, portal),
+            msaa.copied().unwrap_or_default(),
+            PortalCubemapCamera {
+                portal: entity,
+                face,
+            },
+        ));
+        if let Some(render_layers) = portal.render_layers.clone() {
+            camera.insert(render_layers);
+        }
+        face += 1;
+        camera.id()
+    });
+
+    let [forward, right, left, up, down] = face_images;
+    let material = materials.add(PortalCubemapMaterial {
+        portal_from_world: portal_from_world(portal_transform),
+        forward,
+        right,
+        left,
+        up,
+        down,
+        cull_mode: portal.cull_mode,
+    });
+    commands
+        .entity(entity)
+        .insert((PortalCubemapCameras(cameras), MeshMaterial3d(material)));
+    // A `PortalMaterial` may have been inserted if the `PortalCubemap` was added to an existing
+    // portal
+    commands
+        .entity(entity)
+        .remove::<MeshMaterial3d<PortalMaterial>>();
+}
+
+/// System that despawns the cameras of a [`PortalCubemap`] when it (or its [`Portal`]) is removed.
+fn despawn_cubemap(
+    trigger: Trigger<OnRemove, (Portal, PortalCubemap)>,
+    mut commands: Commands,
+    cameras_query: Query<&PortalCubemapCameras>,
+) {
+    let entity = trigger.entity();
+    let Ok(cameras) = cameras_query.get(entity) else {
+        return;
+    };
+    for &camera in &cameras.0 {
+        commands.entity(camera).despawn_recursive();
+    }
+    commands
+        .entity(entity)
+        .remove::<(PortalCubemapCameras, MeshMaterial3d<PortalCubemapMaterial>)>();
+}
+
+/// System that activates the cameras of a [`PortalCubemap`] in place of its
+/// [`Portal::linked_camera`], based on [`Portal::enabled`] and [`PortalCulled`].
+fn update_cubemap_cameras_active(
+    portal_query: Query<(&Portal, Option<&PortalCulled>, &PortalCubemapCameras)>,
+    mut camera_query: Query<&mut Camera>,
+) {
+    for (portal, culled, cameras) in &portal_query {
+        let is_active = portal.enabled && !culled.is_some_and(PortalCulled::is_culled);
+        for &camera in &cameras.0 {
+            let Ok(mut camera) = camera_query.get_mut(camera) else {
+                continue;
+            };
+            if camera.is_active != is_active {
+                camera.is_active = is_active;
+            }
+        }
+
+        if let Some(mut linked_camera) = portal
+            .linked_camera
+            .and_then(|camera| camera_query.get_mut(camera).ok())
+        {
+            if linked_camera.is_active {
+                linked_camera.is_active = false;
+            }
+        }
+    }
+}
+
+/// System that places the cameras of a [`PortalCubemap`] at the position of [`Portal::target`],
+/// each facing along an axis of the target.
+fn update_cubemap_camera_transforms(
+    portal_query: Query<(&Portal, &PortalCubemapCameras)>,
+    mut camera_query: Query<(&mut GlobalTransform, &mut Transform), With<PortalCubemapCamera>>,
+    global_transform_query: Query<&GlobalTransform, Without<PortalCubemapCamera>>,
+) {
+    for (portal, cameras) in &portal_query {
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+        let (_, target_rotation, target_translation) =
+            target_transform.to_scale_rotation_translation();
+
+        for (&camera, (direction, up)) in cameras.0.iter().zip(FACES) {
+            let Ok((mut global_transform, mut transform)) = camera_query.get_mut(camera) else {
+                continue;
+            };
+            *transform = Transform::from_translation(target_translation).with_rotation(
+                target_rotation * Transform::IDENTITY.looking_to(direction, up).rotation,
+            );
+            *global_transform = GlobalTransform::from(*transform);
+        }
+    }
+}
+
+/// System that keeps [`PortalCubemapMaterial::portal_from_world`] in sync with the rotation of its
+/// [`Portal`].
+fn update_cubemap_materials(
+    portal_query: Query<
+        (&GlobalTransform, &MeshMaterial3d<PortalCubemapMaterial>),
+        (With<Portal>, Changed<GlobalTransform>),
+    >,
+    mut materials: ResMut<Assets<PortalCubemapMaterial>>,
+) {
+    for (portal_transform, material_handle) in &portal_query {
+        let portal_from_world = portal_from_world(portal_transform);
+        let Some(material) = materials.get(material_handle) else {
+            continue;
+        };
+        if material.portal_from_world == portal_from_world {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.portal_from_world = portal_from_world;
+        }
+    }
+}
+
+/// Returns the inverse of the rotation of `portal_transform`, see
+/// [`PortalCubemapMaterial::portal_from_world`].
+fn portal_from_world(portal_transform: &GlobalTransform) -> Mat3 {
+    let (_, rotation, _) = portal_transform.to_scale_rotation_translation();
+    Mat3::from_quat(rotation.inverse())
+}
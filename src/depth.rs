@@ -0,0 +1,341 @@
+//! Reading the depth of the view through a [`Portal`], e.g. for custom effects applied to it.
+//!
+//! Set [`Portal::depth_image`] to have the depth rendered by the portal's [`PortalCamera`] copied
+//! into a [`PortalDepthImage`] every frame, which materials and other effects can sample. The
+//! camera's [`Camera3d::depth_texture_usages`] are extended to allow this, which also lets render
+//! graph nodes running for the portal camera read its [`ViewDepthTexture`] directly.
+//!
+//! Only the depth of [`Portal::linked_camera`] is copied, not that of deeper levels of
+//! [`Portal::recursion_depth`]. The copy requires the portal camera not to be multisampled, so
+//! [`Portal::primary_camera`] should have [`Msaa::Off`]. 2D portals aren't supported, and neither
+//! are devices that can't copy depth textures, such as those using OpenGL (including WebGL2), in
+//! which case the image stays blank.
+
+use bevy::{
+    core_pipeline::core_3d::{
+        graph::{Core3d, Node3d},
+        prepare_core_3d_depth_textures,
+    },
+    ecs::query::QueryItem,
+    image::{TextureFormatPixelInfo, Volume},
+    prelude::*,
+    render::{
+        extract_component::{ExtractComponent, ExtractComponentPlugin},
+        render_asset::RenderAssets,
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, RenderLabel, ViewNode, ViewNodeRunner,
+        },
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, DownlevelFlags, Extent3d, ImageCopyBuffer,
+            ImageCopyTexture, ImageDataLayout, Origin3d, TextureAspect, TextureDescriptor,
+            TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderAdapter, RenderContext, RenderDevice},
+        texture::GpuImage,
+        view::ViewDepthTexture,
+        Render, RenderApp, RenderSet,
+    },
+};
+
+use crate::{
+    camera::{PortalCamera, PortalCameraSystems, PortalImage, PortalImagesResized},
+    Portal,
+};
+
+/// Plugin that copies the depth of [`Portal`]s with [`Portal::depth_image`] set into a
+/// [`PortalDepthImage`].
+pub struct PortalDepthPlugin;
+
+impl Plugin for PortalDepthPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(ExtractComponentPlugin::<PortalDepthImage>::default())
+            .add_systems(
+                PreUpdate,
+                resize_depth_images
+                    .run_if(on_event::<PortalImagesResized>)
+                    .after(PortalCameraSystems::ResizeImage),
+            )
+            .add_systems(PostUpdate, spawn_depth_images)
+            .add_observer(despawn_depth_image)
+            .register_type::<PortalDepthImage>();
+
+        let Some(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_systems(
+                Render,
+                prepare_depth_buffers
+                    .in_set(RenderSet::PrepareResources)
+                    .after(prepare_core_3d_depth_textures),
+            )
+            .add_render_graph_node::<ViewNodeRunner<PortalDepthNode>>(Core3d, PortalDepthLabel)
+            .add_render_graph_edges(
+                Core3d,
+                (
+                    Node3d::EndMainPass,
+                    PortalDepthLabel,
+                    Node3d::EndMainPassPostProcessing,
+                ),
+            );
+    }
+}
+
+/// Component that stores the depth of the view through a [`Portal`], see the
+/// [module docs](self).
+///
+/// Like [`PortalImage`], this is present on both the [`Portal`] entity and its
+/// [`Portal::linked_camera`]. The image is the same size as the [`PortalImage`], and stores the
+/// depth of each pixel in its [`TextureFormat::R32Float`] red channel, in Bevy's reverse-Z
+/// convention: `1.0` at the near plane, and `0.0` at infinity (or where nothing was rendered).
+#[derive(Component, Reflect, Clone, Debug, Deref)]
+#[reflect(Component)]
+pub struct PortalDepthImage(pub Handle<Image>);
+
+impl ExtractComponent for PortalDepthImage {
+    type QueryData = &'static Self;
+    type QueryFilter = With<PortalCamera>;
+    type Out = Self;
+
+    fn extract_component(depth_image: QueryItem<'_, Self::QueryData>) -> Option<Self> {
+        Some(depth_image.clone())
+    }
+}
+
+/// System that creates a [`PortalDepthImage`] for the [`Portal::linked_camera`] of each portal
+/// with [`Portal::depth_image`] set, once the camera is spawned.
+///
+/// The camera's depth texture is given the [`TextureUsages::COPY_SRC`] and
+/// [`TextureUsages::TEXTURE_BINDING`] usages. A warning is logged if the camera is multisampled,
+/// since its depth can't be copied then.
+fn spawn_depth_images(
+    mut commands: Commands,
+    mut camera_query: Query<
+        (
+            Entity,
+            &PortalCamera,
+            &PortalImage,
+            &mut Camera3d,
+            Option<&Msaa>,
+        ),
+        Added<PortalCamera>,
+    >,
+    portal_query: Query<&Portal>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (camera, portal_camera, portal_image, mut camera_3d, msaa) in &mut camera_query {
+        let entity = portal_camera.0;
+        let Ok(portal) = portal_query.get(entity) else {
+            continue;
+        };
+        if !portal.depth_image || portal.linked_camera != Some(camera) {
+            continue;
+        }
+        if msaa.is_some_and(|msaa| *msaa != Msaa::Off) {
+            warn!(
+                "portal {entity} has depth_image set, but its camera is multisampled; add \
+                Msaa::Off to its primary camera for the depth to be copied"
+            );
+            continue;
+        }
+        let Some(size) = images
+            .get(&portal_image.0)
+            .map(|image| image.texture_descriptor.size)
+        else {
+            continue;
+        };
+
+        camera_3d.depth_texture_usages = (TextureUsages::from(camera_3d.depth_texture_usages)
+            | TextureUsages::COPY_SRC
+            | TextureUsages::TEXTURE_BINDING)
+            .into();
+
+        let depth_image = PortalDepthImage(images.add(new_depth_image(size)));
+        commands.entity(camera).insert(depth_image.clone());
+        commands.entity(entity).insert(depth_image);
+    }
+}
+
+/// System that resizes [`PortalDepthImage`]s to match the [`PortalImage`] of their camera when
+/// portal images are resized.
+fn resize_depth_images(
+    camera_query: Query<(&PortalImage, &PortalDepthImage), With<PortalCamera>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (portal_image, depth_image) in &camera_query {
+        let Some(size) = images
+            .get(&portal_image.0)
+            .map(|image| image.texture_descriptor.size)
+        else {
+            continue;
+        };
+        let needs_resize = images
+            .get(&depth_image.0)
+            .is_some_and(|image| image.texture_descriptor.size != size);
+        if needs_resize {
+            if let Some(image) = images.get_mut(&depth_image.0) {
+                image.resize(size);
+            }
+        }
+    }
+}
+
+/// System that removes the [`PortalDepthImage`] of a [`Portal`] entity when the [`Portal`]
+/// component is removed from it, allowing the image to be freed along with the portal's camera.
+fn despawn_depth_image(trigger: Trigger<OnRemove, Portal>, mut commands: Commands) {
+    if let Some(mut entity_commands) = commands.get_entity(trigger.entity()) {
+        entity_commands.remove::<PortalDepthImage>();
+    }
+}
+
+/// Returns a blank [`PortalDepthImage`] of the given `size`.
+fn new_depth_image(size: Extent3d) -> Image {
+    // Depth formats can't be written to from the CPU, so Bevy couldn't upload a depth image
+    let format = TextureFormat::R32Float;
+    Image {
+        data: vec![0; size.volume() * format.pixel_size()],
+        texture_descriptor: TextureDescriptor {
+            label: Some("portal_depth_image"),
+            size,
+            dimension: TextureDimension::D2,
+            format,
+            mip_level_count: 1,
+            sample_count: 1,
+            // `COPY_SRC` allows the image to be read back, like portal images
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        },
+        ..default()
+    }
+}
+
+/// Buffer that the depth of a portal camera is copied through on its way to its
+/// [`PortalDepthImage`], since a depth texture can't be copied into a color texture directly.
+#[derive(Component)]
+struct PortalDepthBuffer {
+    buffer: Buffer,
+    bytes_per_row: u32,
+}
+
+/// System that creates a [`PortalDepthBuffer`] for each portal camera with a [`PortalDepthImage`],
+/// re-creating it whenever the camera's depth texture is resized.
+///
+/// If the device can't copy depth textures into buffers (e.g. on OpenGL), no buffers are created
+/// and a warning is logged once, leaving the depth images blank.
+fn prepare_depth_buffers(
+    mut commands: Commands,
+    view_query: Query<
+        (Entity, &ViewDepthTexture, Option<&PortalDepthBuffer>),
+        With<PortalDepthImage>,
+    >,
+    render_device: Res<RenderDevice>,
+    render_adapter: Res<RenderAdapter>,
+    mut warned: Local<bool>,
+) {
+    if view_query.is_empty() {
+        return;
+    }
+    let is_supported = render_adapter
+        .get_downlevel_capabilities()
+        .flags
+        .contains(DownlevelFlags::DEPTH_TEXTURE_AND_BUFFER_COPIES);
+    if !is_supported {
+        if !*warned {
+            warn!(
+                "portal depth images aren't supported, since the render device can't copy depth \
+                textures; they'll stay blank"
+            );
+            *warned = true;
+        }
+        return;
+    }
+
+    for (entity, depth_texture, depth_buffer) in &view_query {
+        let size = depth_texture.texture.size();
+        let bytes_per_row =
+            RenderDevice::align_copy_bytes_per_row(size.width as usize * size_of::<f32>()) as u32;
+        let buffer_size = u64::from(bytes_per_row) * u64::from(size.height);
+        if depth_buffer.is_some_and(|depth_buffer| {
+            depth_buffer.bytes_per_row == bytes_per_row && depth_buffer.buffer.size() == buffer_size
+        }) {
+            continue;
+        }
+
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("portal_depth_buffer"),
+            size: buffer_size,
+            usage: BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        commands.entity(entity).insert(PortalDepthBuffer {
+            buffer,
+            bytes_per_row,
+        });
+    }
+}
+
+#[derive(RenderLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct PortalDepthLabel;
+
+/// Render graph node that copies the depth texture of a portal camera into its
+/// [`PortalDepthImage`], through its [`PortalDepthBuffer`].
+#[derive(Default)]
+struct PortalDepthNode;
+
+impl ViewNode for PortalDepthNode {
+    type ViewQuery = (
+        &'static ViewDepthTexture,
+        &'static PortalDepthImage,
+        &'static PortalDepthBuffer,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (depth_texture, depth_image, depth_buffer): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(gpu_image) = world
+            .resource::<RenderAssets<GpuImage>>()
+            .get(&depth_image.0)
+        else {
+            return Ok(());
+        };
+        // The image lags a frame behind the camera when resized, and a multisampled depth texture
+        // can't be copied
+        let size = depth_texture.texture.size();
+        if depth_texture.texture.sample_count() != 1
+            || gpu_image.size != UVec2::new(size.width, size.height)
+        {
+            return Ok(());
+        }
+
+        let buffer = ImageCopyBuffer {
+            buffer: &depth_buffer.buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(depth_buffer.bytes_per_row),
+                rows_per_image: None,
+            },
+        };
+        let encoder = render_context.command_encoder();
+        encoder.copy_texture_to_buffer(
+            ImageCopyTexture {
+                texture: &depth_texture.texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+                aspect: TextureAspect::DepthOnly,
+            },
+            ImageCopyBuffer {
+                buffer: buffer.buffer,
+                layout: buffer.layout,
+            },
+            size,
+        );
+        encoder.copy_buffer_to_texture(buffer, gpu_image.texture.as_image_copy(), size);
+        Ok(())
+    }
+}
@@ -1,22 +1,118 @@
 //! Gizmos for [`Portal`] debugging.
 
-use bevy::{color::palettes::tailwind::ORANGE_600, prelude::*, render::primitives::Aabb};
+use std::time::Duration;
+
+use bevy::{
+    color::palettes::tailwind::{FUCHSIA_500, LIME_500, ORANGE_600, SKY_500},
+    prelude::*,
+    render::primitives::{Aabb, Frustum},
+};
 
 use crate::Portal;
+#[cfg(feature = "picking")]
+use crate::{camera::remap_transform, picking::PortalPickingRay};
+
+/// The minimum time between warnings about a [`Portal::target`] that can't be drawn, so that a
+/// missing target doesn't flood the log every frame.
+const MISSING_TARGET_WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Configuration for [`Portal`] gizmos, allowing each kind of gizmo to be toggled and recolored.
+///
+/// Modify it through [`GizmoConfigStore::config_mut`].
+#[derive(Reflect, GizmoConfigGroup)]
+pub struct PortalGizmos {
+    /// Whether to draw the [`Aabb`] of each [`Portal`]'s mesh.
+    ///
+    /// Defaults to `true`.
+    pub draw_meshes: bool,
+    /// The color of each [`Portal`]'s mesh [`Aabb`].
+    ///
+    /// Defaults to [`ORANGE_600`].
+    pub mesh_color: Color,
+    /// Whether to draw the plane of each [`Portal`], along with its normal.
+    ///
+    /// Defaults to `true`.
+    pub draw_planes: bool,
+    /// The color of each [`Portal`]'s plane and normal.
+    ///
+    /// Defaults to [`LIME_500`].
+    pub plane_color: Color,
+    /// Whether to draw an arrow at each [`Portal`]'s target, pointing in its forward direction.
+    ///
+    /// Defaults to `true`.
+    pub draw_targets: bool,
+    /// The color of each [`Portal`]'s target arrow.
+    ///
+    /// Defaults to [`ORANGE_600`].
+    pub target_color: Color,
+    /// Whether to draw an arrow at each [`Portal::linked_camera`], pointing in its forward
+    /// direction.
+    ///
+    /// Defaults to `true`.
+    pub draw_cameras: bool,
+    /// The color of each [`Portal::linked_camera`] arrow.
+    ///
+    /// Defaults to [`SKY_500`].
+    pub camera_color: Color,
+    /// Whether to draw the wireframe of each [`Portal::linked_camera`]'s [`Frustum`].
+    ///
+    /// Defaults to `true`.
+    pub draw_frusta: bool,
+    /// The color of each [`Portal::linked_camera`]'s [`Frustum`].
+    ///
+    /// Defaults to [`SKY_500`].
+    pub frustum_color: Color,
+    /// Whether to draw the last ray cast through each [`Portal`] for picking, the point at which it
+    /// hit the portal, and where that point (and the ray's direction) maps to on the other side.
+    ///
+    /// Requires the `picking` feature.
+    ///
+    /// Defaults to `true`.
+    pub draw_picking: bool,
+    /// The color of the picking gizmos.
+    ///
+    /// Defaults to [`FUCHSIA_500`].
+    pub picking_color: Color,
+}
 
-#[derive(Reflect, Default, GizmoConfigGroup)]
-pub struct PortalGizmos;
+impl Default for PortalGizmos {
+    fn default() -> Self {
+        Self {
+            draw_meshes: true,
+            mesh_color: ORANGE_600.into(),
+            draw_planes: true,
+            plane_color: LIME_500.into(),
+            draw_targets: true,
+            target_color: ORANGE_600.into(),
+            draw_cameras: true,
+            camera_color: SKY_500.into(),
+            draw_frusta: true,
+            frustum_color: SKY_500.into(),
+            draw_picking: true,
+            picking_color: FUCHSIA_500.into(),
+        }
+    }
+}
 
 /// Gizmo plugin for [`Portal`]s.
 ///
-/// These gizmos help visualize aspects like [`Portal`] meshes and where the
-/// [`Portal::target_transform`] is located (along with its facing direction).
+/// These gizmos help visualize aspects like [`Portal`] meshes, the plane of each [`Portal`] (along
+/// with its normal), and where each portal's target ([`Portal::target`], or
+/// [`Portal::target_transform`] if set) is located (along with its facing direction). With the `picking` feature, rays cast through portals for picking are shown too.
 pub struct PortalGizmosPlugin;
 
 impl Plugin for PortalGizmosPlugin {
     fn build(&self, app: &mut App) {
-        app.init_gizmo_group::<PortalGizmos>()
-            .add_systems(Update, (debug_portal_meshes, debug_portal_cameras));
+        app.init_gizmo_group::<PortalGizmos>().add_systems(
+            Update,
+            (
+                debug_portal_meshes,
+                debug_portal_planes,
+                debug_portal_cameras,
+            ),
+        );
+        #[cfg(feature = "picking")]
+        app.add_systems(Update, debug_portal_picking);
     }
 }
 
@@ -25,27 +121,188 @@ fn debug_portal_meshes(
     mut gizmos: Gizmos<PortalGizmos>,
     portal_query: Query<(&Transform, &Aabb), With<Portal>>,
 ) {
+    if !gizmos.config_ext.draw_meshes {
+        return;
+    }
+
+    let color = gizmos.config_ext.mesh_color;
     for (&transform, aabb) in &portal_query {
         let transform = Transform {
             scale: (aabb.half_extents * 2.0).into(),
             ..transform
         };
-        gizmos.cuboid(transform, ORANGE_600);
+        gizmos.cuboid(transform, color);
+    }
+}
+
+/// System that renders a circle on the plane of each [`Portal`] (see [`Portal::plane`]), along
+/// with an arrow indicating the plane's normal.
+///
+/// The circle is sized to fit the portal's mesh, if it has an [`Aabb`], and is offset according to
+/// [`Portal::plane_offset`].
+fn debug_portal_planes(
+    mut gizmos: Gizmos<PortalGizmos>,
+    portal_query: Query<(&Portal, &GlobalTransform, Option<&Aabb>)>,
+) {
+    if !gizmos.config_ext.draw_planes {
+        return;
+    }
+
+    let color = gizmos.config_ext.plane_color;
+    for (portal, global_transform, aabb) in &portal_query {
+        let global_transform = portal.plane_transform(global_transform, aabb);
+        let (scale, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let radius =
+            aabb.map_or(1.0, |aabb| aabb.half_extents.truncate().length()) * scale.max_element();
+
+        gizmos.circle(Isometry3d::new(translation, rotation), radius, color);
+
+        let normal = Portal::plane(&global_transform).normal;
+        gizmos.arrow(translation, translation + normal * radius, color);
     }
 }
-/// System that renders arrows indicating the translation and rotation of [`PortalCamera`]s.
+
+/// System that renders the [`PortalPickingRay`] of each [`Portal`], along with the point it maps to
+/// on the other side of the portal.
+#[cfg(feature = "picking")]
+fn debug_portal_picking(
+    mut gizmos: Gizmos<PortalGizmos>,
+    portal_query: Query<(&Portal, &GlobalTransform, &PortalPickingRay)>,
+    global_transform_query: Query<&GlobalTransform>,
+) {
+    if !gizmos.config_ext.draw_picking {
+        return;
+    }
+
+    let color = gizmos.config_ext.picking_color;
+    for (portal, portal_transform, &PortalPickingRay { ray, point }) in &portal_query {
+        gizmos.line(ray.origin, point, color);
+        gizmos.sphere(Isometry3d::from_translation(point), 0.05, color);
+
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+        let hit_transform = Transform::from_translation(point)
+            .with_rotation(Quat::from_rotation_arc(Vec3::NEG_Z, *ray.direction));
+        let remapped = remap_transform(portal_transform, &target_transform, &hit_transform);
+        gizmos.sphere(
+            Isometry3d::from_translation(remapped.translation),
+            0.05,
+            color,
+        );
+        gizmos.arrow(
+            remapped.translation,
+            remapped.translation + remapped.forward() * 0.5,
+            color,
+        );
+    }
+}
+
+/// System that renders arrows indicating the translation and rotation of [`Portal::target`]s and
+/// [`PortalCamera`]s, along with the wireframe of each [`PortalCamera`]'s [`Frustum`].
+///
+/// The frustum is computed from the camera's projection, so it includes the oblique near plane lying
+/// on the plane of [`Portal::target`].
+///
+/// [`PortalCamera`]: crate::camera::PortalCamera
 fn debug_portal_cameras(
     mut gizmos: Gizmos<PortalGizmos>,
-    portal_query: Query<&Portal>,
+    portal_query: Query<(Entity, &Portal)>,
     global_transform_query: Query<&GlobalTransform>,
+    camera_query: Query<(&GlobalTransform, &Frustum)>,
+    time: Res<Time<Real>>,
+    mut last_warned: Local<Option<Duration>>,
 ) {
-    for portal in &portal_query {
-        let transform = global_transform_query
-            .get(portal.target)
-            .map(GlobalTransform::compute_transform)
-            .expect("target should have GlobalTransform");
-        let start = transform.translation;
-        let end = start + transform.forward() * 0.5;
-        gizmos.arrow(start, end, ORANGE_600);
+    let PortalGizmos {
+        draw_targets,
+        target_color,
+        draw_cameras,
+        camera_color,
+        draw_frusta,
+        frustum_color,
+        ..
+    } = *gizmos.config_ext;
+
+    for (entity, portal) in &portal_query {
+        if draw_targets {
+            match portal.get_target_transform(&global_transform_query) {
+                Some(transform) => {
+                    let start = transform.translation();
+                    let end = start + transform.forward() * 0.5;
+                    gizmos.arrow(start, end, target_color);
+                }
+                // The target may be missing for a frame, e.g. while it's being respawned
+                None => {
+                    let now = time.elapsed();
+                    if last_warned.is_none_or(|last| now - last >= MISSING_TARGET_WARNING_INTERVAL)
+                    {
+                        warn!(
+                            "not drawing the target of portal {entity}, since target {} has no \
+                            GlobalTransform",
+                            portal.target
+                        );
+                        *last_warned = Some(now);
+                    }
+                }
+            }
+        }
+
+        let Some((camera_transform, frustum)) = portal
+            .linked_camera
+            .and_then(|camera| camera_query.get(camera).ok())
+        else {
+            continue;
+        };
+        if draw_cameras {
+            let start = camera_transform.translation();
+            let end = start + camera_transform.forward() * 0.5;
+            gizmos.arrow(start, end, camera_color);
+        }
+
+        let Some(corners) = frustum_corners(frustum).filter(|_| draw_frusta) else {
+            continue;
+        };
+        // The near plane, far plane, and the edges connecting them
+        for i in 0..4 {
+            let j = (i + 1) % 4;
+            gizmos.line(corners[i], corners[j], frustum_color);
+            gizmos.line(corners[i + 4], corners[j + 4], frustum_color);
+            gizmos.line(corners[i], corners[i + 4], frustum_color);
+        }
+    }
+}
+
+/// Returns the corners of `frustum`, with the near plane's corners followed by the far plane's,
+/// each in winding order.
+///
+/// Returns `None` if any of the corners can't be determined, e.g. if the frustum is infinite.
+fn frustum_corners(frustum: &Frustum) -> Option<[Vec3; 8]> {
+    let [left, right, bottom, top, near, far] =
+        frustum.half_spaces.map(|half_space| half_space.normal_d());
+    let sides = [(left, bottom), (right, bottom), (right, top), (left, top)];
+
+    let mut corners = [Vec3::ZERO; 8];
+    for (i, plane) in [near, far].into_iter().enumerate() {
+        for (j, &(a, b)) in sides.iter().enumerate() {
+            corners[i * 4 + j] = plane_intersection(plane, a, b)?;
+        }
     }
+    Some(corners)
+}
+
+/// Returns the point at which the planes `a`, `b`, and `c` intersect, if there is one.
+///
+/// Each plane is represented by its normal (`xyz`) and distance (`w`).
+fn plane_intersection(a: Vec4, b: Vec4, c: Vec4) -> Option<Vec3> {
+    let (normal_a, normal_b, normal_c) = (a.truncate(), b.truncate(), c.truncate());
+    let b_cross_c = normal_b.cross(normal_c);
+    let denominator = normal_a.dot(b_cross_c);
+    if denominator.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    let point =
+        -(a.w * b_cross_c + b.w * normal_c.cross(normal_a) + c.w * normal_a.cross(normal_b))
+            / denominator;
+    point.is_finite().then_some(point)
 }
@@ -1,22 +1,178 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "audio")]
+pub mod audio;
 pub mod camera;
+#[cfg(feature = "cubemap")]
+pub mod cubemap;
+pub mod depth;
 #[cfg(feature = "gizmos")]
 pub mod gizmos;
 pub mod material;
+#[cfg(feature = "2d")]
+pub mod material_2d;
 #[cfg(feature = "picking")]
 pub mod picking;
+pub mod projection;
+mod reflect;
+pub mod snapshot;
+#[cfg(feature = "teleport")]
+pub mod teleport;
+#[cfg(feature = "ui")]
+pub mod ui;
+pub mod viewers;
 
-use bevy::{app::PluginGroupBuilder, prelude::*, render::render_resource::Face};
+use bevy::{
+    app::PluginGroupBuilder,
+    ecs::{query::QueryFilter, system::SystemParam},
+    math::Affine2,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        primitives::Aabb,
+        render_resource::{Face, TextureFormat},
+        view::RenderLayers,
+    },
+};
+
+/// A plugin that provides the required systems to make a [`Portal`] work.
+///
+/// This adds [`camera::PortalCameraPlugin`], [`material::PortalMaterialPlugin`],
+/// [`depth::PortalDepthPlugin`], [`snapshot::PortalSnapshotPlugin`], and
+/// [`viewers::PortalViewersPlugin`] (along with
+/// `material_2d::PortalMaterial2dPlugin` if the `2d` feature is enabled, `ui::PortalUiPlugin` if
+/// the `ui` feature is, and `cubemap::PortalCubemapPlugin` if the `cubemap` feature is). Prefer
+/// [`PortalPlugins`] if you need to disable or replace any of them.
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            camera::PortalCameraPlugin::default(),
+            material::PortalMaterialPlugin::default(),
+            depth::PortalDepthPlugin,
+            snapshot::PortalSnapshotPlugin,
+            viewers::PortalViewersPlugin,
+        ));
+        #[cfg(feature = "2d")]
+        app.add_plugins(material_2d::PortalMaterial2dPlugin);
+        #[cfg(feature = "ui")]
+        app.add_plugins(ui::PortalUiPlugin);
+        #[cfg(feature = "cubemap")]
+        app.add_plugins(cubemap::PortalCubemapPlugin);
+    }
+}
 
 /// A group of plugins that provides the required systems to make a [`Portal`] work.
+///
+/// This is the preferred way of adding portals to an app, since individual plugins can be disabled
+/// (e.g. to supply your own material for every portal). Use [`PortalPlugin`] if you'd rather add a
+/// single [`Plugin`].
 pub struct PortalPlugins;
 
 impl PluginGroup for PortalPlugins {
     fn build(self) -> PluginGroupBuilder {
-        PluginGroupBuilder::start::<Self>()
-            .add(camera::PortalCameraPlugin)
-            .add(material::PortalMaterialPlugin)
+        let group = PluginGroupBuilder::start::<Self>()
+            .add(camera::PortalCameraPlugin::default())
+            .add(material::PortalMaterialPlugin::default())
+            .add(depth::PortalDepthPlugin)
+            .add(snapshot::PortalSnapshotPlugin)
+            .add(viewers::PortalViewersPlugin);
+        #[cfg(feature = "2d")]
+        let group = group.add(material_2d::PortalMaterial2dPlugin);
+        #[cfg(feature = "ui")]
+        let group = group.add(ui::PortalUiPlugin);
+        #[cfg(feature = "cubemap")]
+        let group = group.add(cubemap::PortalCubemapPlugin);
+        group
+    }
+}
+
+/// Extension trait for [`Commands`] to spawn [`Portal`]s.
+pub trait PortalCommandsExt {
+    /// Spawns a [`Portal`] with a given `mesh` and `transform`, returning its [`EntityCommands`].
+    ///
+    /// This wires up the common case of a 3D portal, so the mesh can't be forgotten. Settings like
+    /// [`Portal::cull_mode`] can be overridden on the given `portal` beforehand, and any other
+    /// components (e.g. [`RenderLayers`]) can be inserted through the returned [`EntityCommands`].
+    fn spawn_portal(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+    ) -> EntityCommands<'_>;
+
+    /// Spawns a [`Portal`] like [`PortalCommandsExt::spawn_portal`], along with the entity of its
+    /// [`camera::PortalCamera`], returning the portal and camera entities.
+    ///
+    /// Normally [`Portal::linked_camera`] is only set once the [`Portal`] is added to the world.
+    /// Here, the camera's entity is reserved up front, so components can be inserted on it (e.g.
+    /// a picking marker) in the same frame. The camera's own components are still inserted once
+    /// the [`Portal`] is added.
+    fn spawn_portal_with_camera(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+    ) -> (Entity, Entity);
+
+    /// Spawns a [`Portal`] like [`PortalCommandsExt::spawn_portal`], along with its target entity
+    /// at `target_transform`, returning the portal and target entities.
+    ///
+    /// [`Portal::target`] of the given `portal` is replaced with the spawned target, so it can be
+    /// created with any entity, e.g. `Portal::new(primary_camera, Entity::PLACEHOLDER)`. If
+    /// `target_parent` is given, the target is spawned as a child of it, with `target_transform`
+    /// relative to it, so that it follows the parent around (e.g. a shape the portal looks at).
+    fn spawn_portal_with_target(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+        target_transform: Transform,
+        target_parent: Option<Entity>,
+    ) -> (Entity, Entity);
+}
+
+impl PortalCommandsExt for Commands<'_, '_> {
+    fn spawn_portal(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+    ) -> EntityCommands<'_> {
+        self.spawn((Mesh3d(mesh), transform, portal))
+    }
+
+    fn spawn_portal_with_camera(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+    ) -> (Entity, Entity) {
+        let camera = self.spawn_empty().id();
+        let portal = Portal {
+            linked_camera: Some(camera),
+            ..portal
+        };
+        (self.spawn_portal(mesh, transform, portal).id(), camera)
+    }
+
+    fn spawn_portal_with_target(
+        &mut self,
+        mesh: Handle<Mesh>,
+        transform: Transform,
+        portal: Portal,
+        target_transform: Transform,
+        target_parent: Option<Entity>,
+    ) -> (Entity, Entity) {
+        // The target is spawned first, so that it exists once the portal is set up
+        let mut target = self.spawn((Name::new("Portal Target"), target_transform));
+        if let Some(target_parent) = target_parent {
+            target.set_parent(target_parent);
+        }
+        let target = target.id();
+        let portal = portal.with_target(target);
+        (self.spawn_portal(mesh, transform, portal).id(), target)
     }
 }
 
@@ -27,22 +183,71 @@ impl PluginGroup for PortalPlugins {
 /// the properties of [`Portal::primary_camera`].
 ///
 /// If [`material::PortalMaterialPlugin`] is enabled, a [`material::PortalMaterial`] is inserted on
-/// the entity, inherting [`Portal::cull_mode`] for convenience.
-///
-/// [`RenderTarget::Image`]: bevy::render::camera::RenderTarget
+/// the entity, inherting [`Portal::cull_mode`] for convenience, unless
+/// [`material::PortalMaterialPlugin::insert_materials`] is disabled.
 #[non_exhaustive]
-#[derive(Component, Reflect, Debug)]
+#[derive(Component, Reflect, Clone, Debug)]
 #[reflect(Component)]
 #[require(Transform)]
 pub struct Portal {
     /// The entity with the primary render [`Camera`].
     ///
     /// In other words, the camera used to look at this portal.
+    ///
+    /// This can be changed at runtime, e.g. to switch to a cutscene camera, in which case the
+    /// portal's cameras and images are updated for the new camera rather than set up again.
+    ///
+    /// If this camera is despawned, the portal is disabled (see [`Portal::enabled`]). To view the
+    /// portal from several cameras, e.g. in split-screen, see [`viewers::PortalViewers`].
+    ///
+    /// # Stereo rendering
+    ///
+    /// A portal renders the view of a single camera. With XR, where each eye is usually a camera
+    /// of its own rendering to a [`RenderTarget::TextureView`], a portal only looks right to the
+    /// eye it follows, and a warning is logged that the view through it is rendered in mono.
+    ///
+    /// To render a portal in stereo, list each eye's camera in [`viewers::PortalViewers`], so that
+    /// each eye only sees the portal rendered from its point of view.
     pub primary_camera: Entity,
     /// The target entity that should be used to decide the camera's position.
     ///
-    /// This entity should contain a [`Transform`] component.
+    /// This entity should contain a [`Transform`] component. If it doesn't when the portal is
+    /// added, a default [`Transform`] is inserted on it and a warning is logged. It is ignored if
+    /// [`Portal::target_transform`] is set.
+    ///
+    /// If the target is scaled differently to the portal, the view through the portal is scaled
+    /// accordingly, e.g. a target with twice the scale makes the portal "bigger on the inside". Only
+    /// uniform scales are supported, non-uniform scales are approximated.
+    ///
+    /// # Performance
+    ///
+    /// Every portal renders its own view (one render pass per level of recursion), even if several
+    /// portals share a target, since the view through a portal depends on where the portal is.
+    ///
+    /// If several portals lie on the same plane and share a target (e.g. a row of windows into the
+    /// same room), consider using a single [`Portal`] instead, and spawning the other meshes as
+    /// children of it at a local `z` of `0.0` with the portal's [`MeshMaterial3d`]. The
+    /// [`material::PortalMaterial`] samples the portal's image in screen space, so any mesh on
+    /// the portal's plane shows the correct view. Note that frustum culling only considers the
+    /// portal's own mesh (see [`camera::PortalCameraPlugin::frustum_culling`]).
     pub target: Entity,
+    /// An inline transform (in world space) to use as the target, instead of the transform of
+    /// [`Portal::target`].
+    ///
+    /// This avoids spawning an otherwise empty entity just to act as the target.
+    ///
+    /// Set to `None` by default, unless the portal was created with [`Portal::from_transform`].
+    pub target_transform: Option<Transform>,
+    /// Whether [`Portal::target`] is kept in place as the mirror image of the portal, facing the
+    /// opposite direction from the same position, every frame.
+    ///
+    /// This makes the portal act as a mirror that can be moved (and rotated) freely, whatever the
+    /// target's parent. The target's [`Transform`] is overwritten, so it shouldn't be moved by
+    /// anything else, and it can't be a [`Portal`] itself. Ignored if
+    /// [`Portal::target_transform`] is set.
+    ///
+    /// Set to `false` by default, unless the portal was created with [`Portal::mirror`].
+    pub mirror_target: bool,
     /// Specifies which side of the portal to cull: "front", "back", or neither.
     ///
     /// If set to `None`, both sides of the portal’s mesh will be rendered.
@@ -52,21 +257,293 @@ pub struct Portal {
     /// # Note
     ///
     /// If you are using `Some(Face::Front)` or `None` here, and your mesh is flat, you should
-    /// consider setting [`Portal::conditionally_flip_near_plane_normal`] to `true`.
-    // TODO: Can this be remotely reflected upstream now that #6042 has landed?
-    #[reflect(ignore)]
+    /// consider setting [`Portal::flip_near_plane_normal`] to [`NearPlaneFlip::Auto`].
+    #[reflect(remote = reflect::OptionFaceRemote)]
     pub cull_mode: Option<Face>,
     /// The entity that has this portal's [`camera::PortalCamera`].
+    ///
+    /// Set when the portal is added to the world. If it is already set to an existing entity at
+    /// that point, the camera is inserted on that entity instead of being spawned (see
+    /// [`PortalCommandsExt::spawn_portal_with_camera`]).
     pub linked_camera: Option<Entity>,
-    /// If set to `true` this will flip the near plane of the [`camera::PortalCamera`]s frustum if
-    /// the primary camera is facing the back face of the portal.
+    /// Whether to flip the near plane of the [`camera::PortalCamera`]s frustum, see
+    /// [`NearPlaneFlip`].
+    ///
+    /// Set [`NearPlaneFlip::Auto`] if you have a bidirectional portal with a flat mesh, i.e. one
+    /// that doesn't have its back face culled.
+    ///
+    /// Set to `NearPlaneFlip::Fixed(false)` by default.
+    pub flip_near_plane_normal: NearPlaneFlip,
+    /// The number of times this portal is rendered through itself.
+    ///
+    /// For every level of recursion an additional [`camera::PortalCamera`] and image is created.
+    /// Beyond the last level, the portal is rendered using the clear color of
+    /// [`Portal::primary_camera`] (or [`Portal::clear_color`], if set).
+    ///
+    /// Set to `0` by default, meaning the portal is not visible through itself.
+    ///
+    /// # Note
+    ///
+    /// A [`camera::PortalCamera`] can't render the image it is rendering to, so the mesh of the
+    /// portal itself must be on [`RenderLayers`] that are not rendered by portal cameras, set with
+    /// [`Portal::render_layers`]. Recursion is skipped (and an error logged) if `render_layers`
+    /// isn't set. Recursion is then provided by meshes that are spawned on the layers starting at
+    /// [`camera::PORTAL_RECURSION_LAYER`].
+    ///
+    /// Only this portal seen through itself is rendered recursively. Another portal seen through
+    /// this one shows its image as rendered for [`Portal::primary_camera`], rather than the view
+    /// from this portal's cameras.
+    pub recursion_depth: u8,
+    /// The scale of the portal's rendered image, relative to the viewport size of
+    /// [`Portal::primary_camera`].
+    ///
+    /// Lower values are cheaper to render, at the cost of quality. For example, a value of `0.5`
+    /// renders the portal at a quarter of the resolution, which can be useful for distant portals.
+    /// The image is always at least 1 pixel wide and tall.
+    ///
+    /// Values greater than `1.0` supersample the portal, e.g. `2.0` renders it at twice the
+    /// resolution in each dimension, smoothing out aliasing on high-DPI displays. Since the image is
+    /// sampled in screen space, its content covers the viewport whatever its resolution, so it's
+    /// never stretched.
+    ///
+    /// Ignored if [`Portal::fixed_resolution`] is set.
+    ///
+    /// Can be changed at runtime (e.g. by a quality setting), in which case the image is resized in
+    /// place.
+    ///
+    /// Set to `1.0` by default.
+    pub resolution_scale: f32,
+    /// A fixed size for the portal's rendered image, in pixels, independent of the viewport size
+    /// of [`Portal::primary_camera`].
+    ///
+    /// This is useful for a deliberately low resolution look, e.g. a security monitor. The image is
+    /// never resized when the window is, and [`Portal::resolution_scale`] is ignored. Each
+    /// dimension is kept at a minimum of 1 pixel. The view keeps the aspect ratio of the primary
+    /// camera's viewport whatever the image's aspect ratio, since the image is stretched over the
+    /// viewport when sampled.
+    ///
+    /// Like [`Portal::resolution_scale`], this can be changed at runtime.
+    ///
+    /// Set to `None` by default, meaning the image size follows the viewport size.
+    pub fixed_resolution: Option<UVec2>,
+    /// The distance from [`Portal::primary_camera`] beyond which the portal isn't rendered.
+    ///
+    /// Past this distance, the portal's [`camera::PortalCamera`]s are deactivated and the last
+    /// rendered frame keeps being shown, which is rarely noticeable for portals that only take up a
+    /// few pixels. For portals at intermediate distances, consider lowering
+    /// [`Portal::resolution_scale`] instead.
+    ///
+    /// Set to `None` by default, meaning the portal is rendered at any distance.
+    pub max_render_distance: Option<f32>,
+    /// Whether the portal is rendered.
+    ///
+    /// Disabling a portal deactivates its [`camera::PortalCamera`]s, rather than despawning them,
+    /// so re-enabling it is cheap. See [`Portal::disabled_color`] for what is shown while
+    /// disabled.
+    ///
+    /// Set to `true` by default.
+    pub enabled: bool,
+    /// The color shown by the portal while it is disabled.
+    ///
+    /// If set to `None`, the last rendered frame is shown instead.
+    ///
+    /// Set to `None` by default.
+    pub disabled_color: Option<Color>,
+    /// The format of the portal's rendered image.
+    ///
+    /// If set to `None`, the format is chosen based on [`Portal::primary_camera`]: if
+    /// [`Camera::hdr`] is enabled, [`TextureFormat::Rgba16Float`] is used, otherwise
+    /// [`TextureFormat::Bgra8UnormSrgb`] is used.
+    ///
+    /// Set to `None` by default.
+    #[reflect(ignore)]
+    pub texture_format: Option<TextureFormat>,
+    /// The color shown by the portal until its camera has rendered the first frame.
+    ///
+    /// If set to `None`, the portal's image starts out zeroed, which is usually transparent black.
+    /// Setting this to a color similar to what is seen through the portal avoids a noticeable
+    /// flash when portals are spawned mid-game.
+    ///
+    /// Set to `None` by default.
+    pub placeholder_color: Option<Color>,
+    /// The [`RenderLayers`] rendered by this portal's [`camera::PortalCamera`]s.
+    ///
+    /// A portal camera can't render the portal it is rendering for, since that portal's mesh
+    /// samples the image being rendered to. Place the portal's mesh on a layer that isn't included
+    /// here (and include that layer on [`Portal::primary_camera`]) to exclude it from its own view.
+    ///
+    /// Only applied when the portal's cameras are spawned. If set to `None`, the cameras render the
+    /// default layer `0`.
+    ///
+    /// Set to `None` by default.
+    pub render_layers: Option<RenderLayers>,
+    /// Whether this portal's [`camera::PortalCamera`]s render the shadows of [`DirectionalLight`]s.
+    ///
+    /// Bevy renders the shadow cascades of each directional light separately for every camera, so
+    /// each portal camera adds the cost of rendering them again, which is often imperceptible
+    /// through a small or distant portal. If set to `false`, nothing is drawn into the cascades of
+    /// the portal's cameras: directional lights still light the view through the portal, but as if
+    /// nothing cast a shadow. Point and spot light shadow maps are shared by all cameras, so they
+    /// don't add any cost per portal and are still rendered.
+    ///
+    /// Set to `true` by default.
+    pub render_shadows: bool,
+    /// Which optional components of [`Portal::primary_camera`] are inherited by this portal's
+    /// [`camera::PortalCamera`]s, such as its skybox.
+    ///
+    /// Only applied when the portal's cameras are spawned.
+    ///
+    /// Inherits everything by default.
+    pub inheritance: camera::PortalInheritance,
+    /// The projection used by this portal's [`camera::PortalCamera`]s, overriding the one of
+    /// [`Portal::primary_camera`].
+    ///
+    /// This allows the portal to render at a different field of view, e.g. to magnify what's seen
+    /// through it. The oblique near plane is still applied on top of this projection (see
+    /// [`projection::PortalProjection`]), and its far plane is replaced by the primary camera's.
+    /// Ignored for 2D portals.
+    ///
+    /// If set to `None`, the primary camera's [`Projection`] is inherited when the cameras are
+    /// spawned.
+    ///
+    /// Set to `None` by default.
+    pub projection: Option<Projection>,
+    /// The color the view through the portal is multiplied by, e.g. to give it a blue or orange
+    /// hue.
+    ///
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. Modify
+    /// [`material::PortalMaterial::tint`] to change it afterwards.
+    ///
+    /// Set to `None` by default, leaving the view unchanged.
+    pub tint: Option<Color>,
+    /// The distance from the edges of the portal's mesh over which it fades out, in UV units.
+    ///
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. See
+    /// [`material::PortalMaterial::edge_fade`] for details.
+    ///
+    /// Set to `0.0` by default, meaning the edges are hard.
+    pub edge_fade: f32,
+    /// A texture whose alpha masks the portal's mesh to the shape of its opening, e.g. a doorway.
+    ///
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. See
+    /// [`material::PortalMaterial::mask`] for details.
+    ///
+    /// Set to `None` by default, meaning the whole mesh shows the view through the portal.
+    pub mask: Option<Handle<Image>>,
+    /// The distance behind the portal's plane over which geometry fades in, in world units, to hide
+    /// the hard seam of objects straddling the portal.
+    ///
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. See
+    /// [`material::PortalMaterial::clip_fade`] for details.
+    ///
+    /// Set to `0.0` by default, meaning objects are cut off sharply at the portal's plane.
+    pub clip_fade: f32,
+    /// A transform applied to the viewport UVs the portal's image is sampled at, e.g. to flip it
+    /// horizontally for a mirror (see [`Portal::with_uv_flip`]).
+    ///
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. See
+    /// [`material::PortalMaterial::uv_transform`] for details.
+    ///
+    /// Set to [`Affine2::IDENTITY`] by default, leaving the UVs unchanged.
+    pub uv_transform: Affine2,
+    /// An animated distortion of the view through the portal, such as a ripple on its surface.
     ///
-    /// This is particularly useful for portals that are flat meshes and don't have their back face
-    /// culled. In other words, set this to `true` if you have a bidirectional portal with a flat
-    /// mesh. Otherwise, set it to `false`.
+    /// Only applied when the portal's [`material::PortalMaterial`] is created. Modify
+    /// [`material::PortalMaterial::distortion_strength`] (and the fields after it) to change it
+    /// afterwards.
+    ///
+    /// Set to `None` by default, meaning the view isn't distorted.
+    pub distortion: Option<material::PortalDistortion>,
+    /// The [`ClearColorConfig`] of this portal's [`camera::PortalCamera`]s, overriding the one of
+    /// [`Portal::primary_camera`].
+    ///
+    /// This gives the view through the portal its own background, e.g. a custom sky for a window
+    /// into another room. It is also used for the innermost level of [`Portal::recursion_depth`].
+    ///
+    /// Only applied when the portal's cameras are spawned. If set to `None`, the primary camera's
+    /// [`Camera::clear_color`] is inherited.
+    ///
+    /// Set to `None` by default.
+    pub clear_color: Option<ClearColorConfig>,
+    /// How much brighter (or darker, if negative) the view through the portal is than that of
+    /// [`Portal::primary_camera`], in EV (stops).
+    ///
+    /// This offsets the [`Exposure`](bevy::render::camera::Exposure) of the portal's
+    /// [`camera::PortalCamera`]s from the one they inherit, e.g. to brighten a portal into a dark
+    /// cave so that it looks lit from the outside. It's kept up to date when changed.
+    ///
+    /// Only exposure can be adjusted per portal: [`AmbientLight`] and other lights are shared by
+    /// every camera, so a differently lit space needs its own lights, limited to the portal's
+    /// cameras with [`Portal::render_layers`].
+    ///
+    /// Set to `0.0` by default, meaning the primary camera's exposure is used as is.
+    pub exposure_compensation: f32,
+    /// The distance of the portal's plane from its translation, along its local z-axis (see
+    /// [`Portal::plane_transform`]).
+    ///
+    /// The plane is used for the oblique near plane of [`camera::PortalCamera`]s, picking, and
+    /// teleporting. Set this if the visible mesh is offset from the portal entity, e.g. a child
+    /// mesh with its own [`Transform`], so that the plane lines up with the mesh's surface.
+    ///
+    /// Set to `None` by default, meaning the plane passes through the center of the portal's mesh
+    /// (its [`Aabb`]), or through its translation if it has no mesh.
+    pub plane_offset: Option<f32>,
+    /// A user-provided target for [`Portal::linked_camera`] to render into, in place of an image
+    /// created (and resized) by the portal.
+    ///
+    /// This lets the view through the portal be fed into custom render graphs or external surfaces,
+    /// e.g. a [`RenderTarget::Image`] whose handle is already known, or a
+    /// [`RenderTarget::TextureView`]. The target is never resized, pooled or freed by the portal. A
+    /// [`camera::PortalImage`] (and in turn a [`material::PortalMaterial`]) is only inserted for an
+    /// image, and [`Portal::recursion_depth`] isn't supported. [`RenderTarget::Window`] isn't
+    /// supported either, and is ignored.
+    ///
+    /// Only applied when the portal's cameras are spawned.
+    ///
+    /// Set to `None` by default, meaning an image sized to [`Portal::primary_camera`]'s viewport is
+    /// created.
+    pub render_target: Option<RenderTarget>,
+    /// How often the portal is rendered, in frames.
+    ///
+    /// Portals that don't need to be smooth, e.g. a distant window or an ambient background, can
+    /// be rendered every few frames to save on their cost. In between, the portal's
+    /// [`camera::PortalCamera`]s are deactivated and the last rendered frame keeps being shown.
+    /// This combines with culling (see [`Portal::max_render_distance`]), which takes precedence.
+    ///
+    /// Set to `1` by default, meaning the portal is rendered every frame. `0` is treated as `1`.
+    pub render_interval: u32,
+    /// Which frame, out of every [`Portal::render_interval`] frames, the portal is rendered on.
+    ///
+    /// Portals with the same interval are rendered on different frames unless they share a phase,
+    /// spreading their cost over several frames. The portal is rendered on the frames where the
+    /// [`camera::PortalFrameCount`] plus the phase is a multiple of the interval.
+    ///
+    /// Set to `None` by default, meaning the phase is derived from the portal's entity, which
+    /// staggers portals without any setup.
+    pub render_phase: Option<u32>,
+    /// Whether to copy the depth of the view through the portal into a
+    /// [`depth::PortalDepthImage`], e.g. for custom effects applied to it.
+    ///
+    /// This requires [`Portal::primary_camera`] to have [`Msaa::Off`], and costs a copy of the
+    /// depth texture every frame the portal is rendered. See the [`depth`] module for details.
+    ///
+    /// Only applied when the portal's cameras are spawned.
+    ///
+    /// Set to `false` by default.
+    pub depth_image: bool,
+    /// Whether to extrapolate the motion of [`Portal::primary_camera`] when positioning the
+    /// portal's [`camera::PortalCamera`]s, advancing them by the primary camera's movement over
+    /// the last frame.
+    ///
+    /// The portal's cameras follow the primary camera as of [`PostUpdate`]. If the primary camera
+    /// is presented with extra latency, or moved after the portal's cameras are positioned, the
+    /// view through the portal lags a frame behind, visible as "swimming" at the portal's edges
+    /// when the camera moves quickly. Enabling this assumes the camera keeps moving as it did over
+    /// the last frame, trading accuracy (the view overshoots when the camera stops or turns
+    /// suddenly) for perceived smoothness, so only enable it if that lag is noticeable.
     ///
     /// Set to `false` by default.
-    pub flip_near_plane_normal: bool,
+    pub extrapolate_motion: bool,
 }
 
 impl Portal {
@@ -82,12 +559,117 @@ impl Portal {
         Self {
             primary_camera,
             target,
+            target_transform: None,
+            mirror_target: false,
             cull_mode: Some(Face::Back),
             linked_camera: None,
-            flip_near_plane_normal: false,
+            flip_near_plane_normal: NearPlaneFlip::default(),
+            recursion_depth: 0,
+            resolution_scale: 1.0,
+            fixed_resolution: None,
+            max_render_distance: None,
+            enabled: true,
+            disabled_color: None,
+            texture_format: None,
+            placeholder_color: None,
+            render_layers: None,
+            render_shadows: true,
+            inheritance: camera::PortalInheritance::default(),
+            projection: None,
+            tint: None,
+            edge_fade: 0.0,
+            mask: None,
+            clip_fade: 0.0,
+            uv_transform: Affine2::IDENTITY,
+            distortion: None,
+            clear_color: None,
+            exposure_compensation: 0.0,
+            plane_offset: None,
+            render_target: None,
+            render_interval: 1,
+            render_phase: None,
+            depth_image: false,
+            extrapolate_motion: false,
+        }
+    }
+
+    /// Creates a new [`Portal`] from a given `primary_camera` and inline `target_transform`, rather
+    /// than a target entity.
+    ///
+    /// [`Portal::target`] is set to [`Entity::PLACEHOLDER`], and is ignored.
+    ///
+    /// # See Also
+    ///
+    /// * [`Portal::target_transform`]
+    #[inline]
+    #[must_use]
+    pub fn from_transform(primary_camera: Entity, target_transform: Transform) -> Self {
+        Self {
+            target_transform: Some(target_transform),
+            ..Self::new(primary_camera, Entity::PLACEHOLDER)
+        }
+    }
+
+    /// Creates a new [`Portal`] that acts as a one-way window from a given `primary_camera` onto
+    /// `target`.
+    ///
+    /// The window is only visible from its front, so the back face is culled and
+    /// [`Portal::flip_near_plane_normal`] is left as `NearPlaneFlip::Fixed(false)`. These are the
+    /// same settings as [`Portal::new`], which leaves them open to being changed.
+    #[inline]
+    #[must_use]
+    pub fn window(primary_camera: Entity, target: Entity) -> Self {
+        Self::new(primary_camera, target)
+            .with_cull_mode(Some(Face::Back))
+            .with_flip_near_plane_normal(NearPlaneFlip::Fixed(false))
+    }
+
+    /// Creates a new [`Portal`] from a given `primary_camera` onto `target` that can be seen (and
+    /// looked through) from both sides, e.g. a doorway between two rooms.
+    ///
+    /// Neither face is culled, and [`Portal::flip_near_plane_normal`] is set to
+    /// [`NearPlaneFlip::Auto`], so that the view through the back face isn't clipped away.
+    #[inline]
+    #[must_use]
+    pub fn bidirectional(primary_camera: Entity, target: Entity) -> Self {
+        Self::new(primary_camera, target)
+            .with_cull_mode(None)
+            .with_flip_near_plane_normal(NearPlaneFlip::Auto)
+    }
+
+    /// Creates a new [`Portal`] that acts as a mirror for a given `primary_camera`.
+    ///
+    /// Rather than requiring a target up front, [`Portal::target`] is set to
+    /// [`Entity::PLACEHOLDER`]. When the portal is added to an entity, a target is spawned as a
+    /// child of it, rotated to face the opposite direction. [`Portal::mirror_target`] is set, so
+    /// the target stays in place relative to the mirror if the mirror moves, even if it's
+    /// reparented.
+    ///
+    /// Mirrors are only visible from their front, so the back face is culled and
+    /// [`Portal::flip_near_plane_normal`] is left as `NearPlaneFlip::Fixed(false)`.
+    #[inline]
+    #[must_use]
+    pub fn mirror(primary_camera: Entity) -> Self {
+        Self {
+            mirror_target: true,
+            ..Self::new(primary_camera, Entity::PLACEHOLDER)
         }
     }
 
+    #[inline]
+    #[must_use]
+    pub fn with_target(mut self, target: Entity) -> Self {
+        self.target = target;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_mirror_target(mut self, mirror_target: bool) -> Self {
+        self.mirror_target = mirror_target;
+        self
+    }
+
     #[inline]
     #[must_use]
     pub fn with_cull_mode(mut self, cull_mode: Option<Face>) -> Self {
@@ -97,8 +679,431 @@ impl Portal {
 
     #[inline]
     #[must_use]
-    pub fn with_flip_near_plane_normal(mut self, with_flip_near_plane_normal: bool) -> Self {
+    pub fn with_flip_near_plane_normal(
+        mut self,
+        with_flip_near_plane_normal: NearPlaneFlip,
+    ) -> Self {
         self.flip_near_plane_normal = with_flip_near_plane_normal;
         self
     }
+
+    #[inline]
+    #[must_use]
+    pub fn with_recursion_depth(mut self, recursion_depth: u8) -> Self {
+        self.recursion_depth = recursion_depth;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_resolution_scale(mut self, resolution_scale: f32) -> Self {
+        self.resolution_scale = resolution_scale;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_fixed_resolution(mut self, fixed_resolution: Option<UVec2>) -> Self {
+        self.fixed_resolution = fixed_resolution;
+        self
+    }
+
+    /// Sets [`Portal::fixed_resolution`] to `resolution`.
+    #[inline]
+    #[must_use]
+    pub fn with_resolution(self, resolution: UVec2) -> Self {
+        self.with_fixed_resolution(Some(resolution))
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_max_render_distance(mut self, max_render_distance: Option<f32>) -> Self {
+        self.max_render_distance = max_render_distance;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_disabled_color(mut self, disabled_color: Option<Color>) -> Self {
+        self.disabled_color = disabled_color;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_texture_format(mut self, texture_format: Option<TextureFormat>) -> Self {
+        self.texture_format = texture_format;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_placeholder_color(mut self, placeholder_color: Option<Color>) -> Self {
+        self.placeholder_color = placeholder_color;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_render_layers(mut self, render_layers: Option<RenderLayers>) -> Self {
+        self.render_layers = render_layers;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_render_shadows(mut self, render_shadows: bool) -> Self {
+        self.render_shadows = render_shadows;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_inheritance(mut self, inheritance: camera::PortalInheritance) -> Self {
+        self.inheritance = inheritance;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_projection(mut self, projection: Option<Projection>) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_tint(mut self, tint: Option<Color>) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_edge_fade(mut self, edge_fade: f32) -> Self {
+        self.edge_fade = edge_fade;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_mask(mut self, mask: Option<Handle<Image>>) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_clip_fade(mut self, clip_fade: f32) -> Self {
+        self.clip_fade = clip_fade;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_uv_transform(mut self, uv_transform: Affine2) -> Self {
+        self.uv_transform = uv_transform;
+        self
+    }
+
+    /// Sets [`Portal::uv_transform`] to mirror the portal's image horizontally if `flip` is `true`,
+    /// or to leave it unchanged otherwise.
+    #[inline]
+    #[must_use]
+    pub fn with_uv_flip(mut self, flip: bool) -> Self {
+        self.uv_transform = if flip {
+            Affine2::from_cols(Vec2::NEG_X, Vec2::Y, Vec2::X)
+        } else {
+            Affine2::IDENTITY
+        };
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_distortion(mut self, distortion: Option<material::PortalDistortion>) -> Self {
+        self.distortion = distortion;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_clear_color(mut self, clear_color: Option<ClearColorConfig>) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_exposure_compensation(mut self, exposure_compensation: f32) -> Self {
+        self.exposure_compensation = exposure_compensation;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_plane_offset(mut self, plane_offset: Option<f32>) -> Self {
+        self.plane_offset = plane_offset;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_render_target(mut self, render_target: Option<RenderTarget>) -> Self {
+        self.render_target = render_target;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_render_interval(mut self, render_interval: u32) -> Self {
+        self.render_interval = render_interval;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_render_phase(mut self, render_phase: Option<u32>) -> Self {
+        self.render_phase = render_phase;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_depth_image(mut self, depth_image: bool) -> Self {
+        self.depth_image = depth_image;
+        self
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn with_extrapolate_motion(mut self, extrapolate_motion: bool) -> Self {
+        self.extrapolate_motion = extrapolate_motion;
+        self
+    }
+
+    /// Returns the [`GlobalTransform`] of this portal's target.
+    ///
+    /// This is [`Portal::target_transform`] if set, otherwise the [`GlobalTransform`] of
+    /// [`Portal::target`] is retrieved from `global_transform_query`.
+    pub fn get_target_transform<F: QueryFilter>(
+        &self,
+        global_transform_query: &Query<&GlobalTransform, F>,
+    ) -> Option<GlobalTransform> {
+        match self.target_transform {
+            Some(target_transform) => Some(target_transform.into()),
+            None => global_transform_query.get(self.target).ok().copied(),
+        }
+    }
+
+    /// Returns the image rendered by [`Portal::linked_camera`], once its camera has been set up.
+    ///
+    /// This is a shorthand for looking up the [`camera::PortalImage`] of the linked camera in
+    /// `world`, e.g. for debug UIs. In systems, prefer [`Portals::image`].
+    pub fn image<'w>(&self, world: &'w World) -> Option<&'w Handle<Image>> {
+        let portal_image = world.get::<camera::PortalImage>(self.linked_camera?)?;
+        Some(&portal_image.0)
+    }
+
+    /// Returns whether [`Portal::linked_camera`] is active in `world`, i.e. the portal is enabled
+    /// and not culled (see [`camera::PortalCulled`]).
+    ///
+    /// Returns `false` if the camera hasn't been set up. In systems, prefer
+    /// [`Portals::is_camera_active`].
+    pub fn is_camera_active(&self, world: &World) -> bool {
+        self.linked_camera
+            .and_then(|camera| world.get::<Camera>(camera))
+            .is_some_and(|camera| camera.is_active)
+    }
+
+    /// Returns the transform of this portal's plane, given the `transform` of the portal (or its
+    /// target) and the [`Aabb`] of the portal's mesh, if any.
+    ///
+    /// This is `transform` moved along its local z-axis by [`Portal::plane_offset`], or by the
+    /// center of `aabb` if unset. Pass the result to [`Portal::plane`] and [`Portal::side`].
+    pub fn plane_transform(
+        &self,
+        transform: &GlobalTransform,
+        aabb: Option<&Aabb>,
+    ) -> GlobalTransform {
+        let offset = self
+            .plane_offset
+            .unwrap_or_else(|| aabb.map_or(0.0, |aabb| aabb.center.z));
+        if offset == 0.0 {
+            return *transform;
+        }
+        transform.mul_transform(Transform::from_xyz(0.0, 0.0, offset))
+    }
+
+    /// Returns where `point`, on the far side of a portal's target, appears to be when seen (or
+    /// heard) through the portal, given the `portal_transform` and `target_transform`.
+    ///
+    /// This is the inverse of how [`camera::PortalCamera`]s are positioned relative to
+    /// [`Portal::primary_camera`], e.g. for placing audio emitters (see the `audio` module).
+    pub fn remap_point(
+        portal_transform: &GlobalTransform,
+        target_transform: &GlobalTransform,
+        point: Vec3,
+    ) -> Vec3 {
+        camera::remap_transform(
+            target_transform,
+            portal_transform,
+            &Transform::from_translation(point),
+        )
+        .translation
+    }
+
+    /// Returns the plane of a portal (or target) with the given `transform`, passing through its
+    /// translation. Use [`Portal::plane_transform`] to account for [`Portal::plane_offset`].
+    ///
+    /// The plane's normal is the transform's forward direction, which is the same plane used for
+    /// the oblique near plane of [`camera::PortalCamera`]s and for picking. Note that the visible
+    /// face of a portal's mesh (e.g. a [`Rectangle`]) faces the opposite direction.
+    pub fn plane(transform: &GlobalTransform) -> InfinitePlane3d {
+        InfinitePlane3d::new(transform.forward())
+    }
+
+    /// Returns the signed distance from the plane of a portal (or target) with the given
+    /// `transform` to `point` (see [`Portal::plane`]).
+    ///
+    /// The distance is positive if `point` lies on the side the plane's normal points to, i.e.
+    /// behind the visible face of the portal's mesh, and negative if it lies in front of it.
+    pub fn side(transform: &GlobalTransform, point: Vec3) -> f32 {
+        (point - transform.translation()).dot(*transform.forward())
+    }
+
+    /// Returns whether a camera with the given `camera_transform` is behind the portal (or target)
+    /// with the given `transform`, i.e. looking at the back of the portal's mesh.
+    ///
+    /// A camera lying exactly on the portal's plane (seeing it edge-on) counts as being behind it.
+    /// This is the test used by [`NearPlaneFlip::Auto`], so pass the result of
+    /// [`Portal::plane_transform`] to account for [`Portal::plane_offset`] in the same way.
+    pub fn is_camera_on_back_side(
+        transform: &GlobalTransform,
+        camera_transform: &GlobalTransform,
+    ) -> bool {
+        Self::side(transform, camera_transform.translation()) >= 0.0
+    }
+}
+
+/// Whether the near plane of a [`Portal`]'s [`camera::PortalCamera`]s is flipped, see
+/// [`Portal::flip_near_plane_normal`].
+///
+/// The near plane lies on the plane of [`Portal::target`], with its normal pointing away from the
+/// portal's visible face. A portal that is seen from behind (e.g. a flat mesh without back face
+/// culling) needs the normal to point the other way, otherwise the portal camera only renders what
+/// is in front of the target, which usually results in a black portal.
+#[derive(Reflect, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NearPlaneFlip {
+    /// The near plane is always flipped if `true`, and never if `false`.
+    Fixed(bool),
+    /// The near plane is flipped whenever [`Portal::primary_camera`] is behind the portal (see
+    /// [`Portal::is_camera_on_back_side`]), which is correct for any double-sided flat portal.
+    Auto,
+}
+
+impl Default for NearPlaneFlip {
+    fn default() -> Self {
+        Self::Fixed(false)
+    }
+}
+
+/// [`SystemParam`] for iterating over all [`Portal`]s, along with their cameras and images.
+///
+/// This saves joining [`Portal`], [`camera::PortalImage`], and [`camera::PortalRecursion`] by hand,
+/// e.g. for debug overlays or tooling that needs each portal's render textures.
+#[derive(SystemParam)]
+pub struct Portals<'w, 's> {
+    portal_query: Query<
+        'w,
+        's,
+        (
+            Entity,
+            &'static Portal,
+            Option<&'static camera::PortalImage>,
+            Option<&'static camera::PortalRecursion>,
+        ),
+    >,
+    camera_query: Query<'w, 's, (&'static camera::PortalCamera, &'static Camera)>,
+}
+
+impl Portals<'_, '_> {
+    /// Returns an iterator over each [`Portal`] entity, along with its [`camera::PortalImage`] if
+    /// its camera has been set up.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &Portal, Option<&camera::PortalImage>)> {
+        self.portal_query
+            .iter()
+            .map(|(entity, portal, portal_image, _)| (entity, portal, portal_image))
+    }
+
+    /// Returns the [`Portal`] on `entity`, along with its [`camera::PortalImage`] if its camera has
+    /// been set up.
+    ///
+    /// Returns `None` if `entity` isn't a portal.
+    pub fn get(&self, entity: Entity) -> Option<(&Portal, Option<&camera::PortalImage>)> {
+        self.portal_query
+            .get(entity)
+            .ok()
+            .map(|(_, portal, portal_image, _)| (portal, portal_image))
+    }
+
+    /// Returns an iterator over the [`camera::PortalCamera`]s of the portal on `entity`, starting
+    /// with [`Portal::linked_camera`] and followed by any [`camera::PortalRecursion::cameras`].
+    ///
+    /// The iterator is empty if `entity` isn't a portal, or its camera hasn't been set up.
+    pub fn cameras(&self, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+        let (portal, recursion) = self
+            .portal_query
+            .get(entity)
+            .ok()
+            .map(|(_, portal, _, recursion)| (portal, recursion))
+            .unzip();
+        let recursion_cameras = recursion
+            .flatten()
+            .into_iter()
+            .flat_map(|recursion| recursion.cameras.iter().copied());
+        portal
+            .and_then(|portal| portal.linked_camera)
+            .into_iter()
+            .chain(recursion_cameras)
+    }
+
+    /// Returns the [`Portal`] entity that the [`camera::PortalCamera`] on `camera` renders for.
+    ///
+    /// Returns `None` if `camera` isn't a portal camera.
+    pub fn portal_of(&self, camera: Entity) -> Option<Entity> {
+        self.camera_query
+            .get(camera)
+            .ok()
+            .map(|(portal_camera, _)| portal_camera.0)
+    }
+
+    /// Returns the image rendered by [`Portal::linked_camera`] of the portal on `entity`.
+    ///
+    /// Returns `None` if `entity` isn't a portal, or its camera hasn't been set up.
+    pub fn image(&self, entity: Entity) -> Option<&Handle<Image>> {
+        self.portal_query
+            .get(entity)
+            .ok()
+            .and_then(|(_, _, portal_image, _)| portal_image)
+            .map(|portal_image| &portal_image.0)
+    }
+
+    /// Returns whether [`Portal::linked_camera`] of the portal on `entity` is active, i.e. the
+    /// portal is enabled and not culled (see [`camera::PortalCulled`]).
+    ///
+    /// Returns `false` if `entity` isn't a portal, or its camera hasn't been set up.
+    pub fn is_camera_active(&self, entity: Entity) -> bool {
+        self.get(entity)
+            .and_then(|(portal, _)| portal.linked_camera)
+            .and_then(|camera| self.camera_query.get(camera).ok())
+            .is_some_and(|(_, camera)| camera.is_active)
+    }
 }
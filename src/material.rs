@@ -1,6 +1,9 @@
+use std::{iter, marker::PhantomData};
+
 use bevy::{
     asset::load_internal_asset,
-    core_pipeline::core_3d::CORE_3D_DEPTH_FORMAT,
+    core_pipeline::{core_3d::CORE_3D_DEPTH_FORMAT, prepass::DepthPrepass},
+    ecs::component::ComponentId,
     pbr::{MaterialPipeline, MaterialPipelineKey},
     prelude::*,
     render::{
@@ -10,19 +13,45 @@ use bevy::{
             RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError, StencilFaceState,
             StencilState,
         },
+        view::RenderLayers,
     },
-    window::WindowResized,
 };
+pub use uniform::PortalMaterialUniform;
 
 use crate::{
-    camera::{PortalCameraSystems, PortalImage},
+    camera::{
+        fill_image, PortalCameraSystems, PortalImage, PortalImagesResized, PortalRecursion,
+        PORTAL_RECURSION_LAYER,
+    },
     Portal,
 };
 
 pub const PORTAL_SHADER_HANDLE: Handle<Shader> =
     Handle::weak_from_u128(115090128739399034051596692516865947112);
 
-pub struct PortalMaterialPlugin;
+pub struct PortalMaterialPlugin {
+    /// Whether to insert a [`PortalMaterial`] on each [`Portal`] once its [`PortalImage`] is
+    /// created.
+    ///
+    /// Disable this to create every portal's material yourself, e.g. with a custom shader, without
+    /// racing the automatic insertion. Once the [`PortalImage`] is added to the portal entity (see
+    /// [`Trigger<OnAdd, PortalImage>`](Trigger)), insert a [`MeshMaterial3d`] whose material
+    /// samples its handle, such as [`PortalMaterial::new`]. Alternatively, implement
+    /// [`PortalTextureSink`] for a custom material to have its texture set automatically.
+    ///
+    /// Recursion meshes (see [`Portal::recursion_depth`]) still use [`PortalMaterial`].
+    ///
+    /// Defaults to `true`.
+    pub insert_materials: bool,
+}
+
+impl Default for PortalMaterialPlugin {
+    fn default() -> Self {
+        Self {
+            insert_materials: true,
+        }
+    }
+}
 
 impl Plugin for PortalMaterialPlugin {
     fn build(&self, app: &mut App) {
@@ -37,33 +66,207 @@ impl Plugin for PortalMaterialPlugin {
             .add_systems(
                 PreUpdate,
                 update_materials::<PortalMaterial>
-                    .run_if(on_event::<WindowResized>)
+                    .run_if(on_event::<PortalImagesResized>)
                     .after(PortalCameraSystems::ResizeImage),
             )
-            .add_observer(spawn_material);
+            .add_systems(
+                PostUpdate,
+                (fill_disabled_portal_images, sync_material_cull_modes),
+            )
+            .add_observer(despawn_material)
+            .add_observer(spawn_recursion_meshes)
+            .add_observer(despawn_recursion_meshes)
+            .init_resource::<PortalTextureSinks>()
+            .register_type::<PortalRecursionMesh>();
+
+        if self.insert_materials {
+            app.add_observer(spawn_material);
+        }
+    }
+}
+
+/// A [`Material`] that can render the image of a [`Portal`], allowing custom portal shaders (e.g.
+/// with ripples or distortion) to be used in place of [`PortalMaterial`].
+///
+/// Add [`PortalTextureSinkPlugin<M>`] for the material, and insert a [`MeshMaterial3d<M>`] on the
+/// [`Portal`] entity. Once the portal's [`PortalImage`] is created, it is handed to the material
+/// through [`PortalTextureSink::set_portal_texture`], and no [`PortalMaterial`] is inserted on the
+/// entity.
+///
+/// The image is the size of the primary camera's viewport, so it should be sampled in screen
+/// space, like `portal.wgsl` does. Recursion meshes (see [`Portal::recursion_depth`]) still use
+/// [`PortalMaterial`].
+pub trait PortalTextureSink: Material {
+    /// Sets the image rendered by the [`Portal`]'s camera.
+    fn set_portal_texture(&mut self, image: Handle<Image>);
+}
+
+impl PortalTextureSink for PortalMaterial {
+    fn set_portal_texture(&mut self, image: Handle<Image>) {
+        self.base_color_texture = Some(image);
+    }
+}
+
+/// Plugin that hands the image of a [`Portal`] to its [`MeshMaterial3d<M>`], see
+/// [`PortalTextureSink`].
+pub struct PortalTextureSinkPlugin<M: PortalTextureSink>(PhantomData<M>);
+
+impl<M: PortalTextureSink> Default for PortalTextureSinkPlugin<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M: PortalTextureSink> Plugin for PortalTextureSinkPlugin<M> {
+    fn build(&self, app: &mut App) {
+        let component_id = app.world_mut().register_component::<MeshMaterial3d<M>>();
+        app.init_resource::<PortalTextureSinks>()
+            .add_systems(
+                PreUpdate,
+                update_materials::<M>
+                    .run_if(on_event::<PortalImagesResized>)
+                    .after(PortalCameraSystems::ResizeImage),
+            )
+            .add_observer(set_portal_texture::<M>);
+        app.world_mut()
+            .resource_mut::<PortalTextureSinks>()
+            .0
+            .push(component_id);
+    }
+}
+
+/// The [`ComponentId`]s of the [`MeshMaterial3d`]s of every [`PortalTextureSink`] (and of any other
+/// component that supplies a portal's material), used to avoid inserting a [`PortalMaterial`] on
+/// portals that have one of them.
+#[derive(Resource, Default)]
+pub(crate) struct PortalTextureSinks(pub(crate) Vec<ComponentId>);
+
+/// System that hands the [`PortalImage`] of a [`Portal`] to its [`MeshMaterial3d<M>`] when the
+/// image is added.
+fn set_portal_texture<M: PortalTextureSink>(
+    trigger: Trigger<OnAdd, PortalImage>,
+    portal_query: Query<(&PortalImage, &MeshMaterial3d<M>), With<Portal>>,
+    mut materials: ResMut<Assets<M>>,
+) {
+    let Ok((portal_image, material_handle)) = portal_query.get(trigger.entity()) else {
+        return;
+    };
+    if let Some(material) = materials.get_mut(material_handle) {
+        material.set_portal_texture(portal_image.0.clone());
     }
 }
 
 /// Material used for a [`Portal`]'s mesh.
-#[derive(Asset, AsBindGroup, Clone, TypePath, Reflect)]
+///
+/// The portal's image is sampled in screen space, at the position of each fragment in the primary
+/// camera's viewport, rather than by the mesh's UVs. This way the image lines up with the scene
+/// around the portal whatever the shape of the mesh. [`PortalMaterial::uv_transform`] can be used
+/// to adjust these viewport UVs, e.g. to flip the image.
+#[derive(Asset, AsBindGroup, Clone, Reflect)]
 #[bind_group_data(PortalMaterialKey)]
+#[uniform(2, PortalMaterialUniform)]
 pub struct PortalMaterial {
     #[texture(0)]
     #[sampler(1)]
     base_color_texture: Option<Handle<Image>>,
+    /// The color the portal's rendered image is multiplied by.
+    ///
+    /// This field's value is initialized from [`Portal::tint`].
+    ///
+    /// Defaults to [`LinearRgba::WHITE`], leaving the image unchanged.
+    pub tint: LinearRgba,
+    /// The distance from the edges of the mesh's UVs over which the portal fades out, in UV units.
+    ///
+    /// A value greater than `0.0` uses [`AlphaMode::Blend`] to feather the portal's border. This
+    /// requires the mesh to have UVs in the `0..1` range, like a [`Rectangle`].
+    ///
+    /// This field's value is initialized from [`Portal::edge_fade`].
+    ///
+    /// Defaults to `0.0`, meaning the edges are hard.
+    pub edge_fade: f32,
+    /// A texture whose alpha masks the portal to the shape of its opening, sampled at the mesh's
+    /// UVs, e.g. for a doorway or circular window on a [`Rectangle`].
+    ///
+    /// Setting this uses [`AlphaMode::Blend`], so that the mask's edges can be soft. A mesh shaped
+    /// like the opening should be preferred where possible, since it's cheaper, and picking (see
+    /// the `picking` module) is done against the mesh rather than the mask.
+    ///
+    /// This field's value is initialized from [`Portal::mask`].
+    ///
+    /// Defaults to `None`, meaning the whole mesh shows the portal.
+    #[texture(3)]
+    #[sampler(4)]
+    pub mask: Option<Handle<Image>>,
+    /// The distance behind the portal's plane over which geometry seen around the portal fades
+    /// out, in world units, softening the seam where an object straddles the portal.
+    ///
+    /// Where the scene behind the mesh is closer to it than this distance, the portal is blended
+    /// with the scene, so that the part of the object behind the portal fades out instead of being
+    /// cut off sharply. A value greater than `0.0` uses [`AlphaMode::Blend`], which also keeps the
+    /// mesh out of the depth prepass.
+    ///
+    /// This reads the depth of the scene from the depth prepass, so it requires
+    /// [`DepthPrepass`] on [`Portal::primary_camera`], and has no effect otherwise. It isn't
+    /// supported on the OpenGL backend, which can't read from depth textures in shaders.
+    ///
+    /// This field's value is initialized from [`Portal::clip_fade`].
+    ///
+    /// Defaults to `0.0`, meaning objects are cut off sharply at the portal's plane.
+    ///
+    /// [`DepthPrepass`]: bevy::core_pipeline::prepass::DepthPrepass
+    pub clip_fade: f32,
+    /// A transform applied to the viewport UVs the portal's image is sampled at, e.g. to flip or
+    /// tile it.
+    ///
+    /// The UVs range from `(0.0, 0.0)` at the top left of the viewport to `(1.0, 1.0)` at the bottom
+    /// right. For example, [`Portal::with_uv_flip`] sets this to `Mat3::from(Affine2::from_cols(
+    /// Vec2::NEG_X, Vec2::Y, Vec2::X))`, mirroring the image horizontally.
+    ///
+    /// This field's value is initialized from [`Portal::uv_transform`].
+    ///
+    /// Defaults to [`Mat3::IDENTITY`], leaving the UVs unchanged.
+    pub uv_transform: Mat3,
+    /// How far the portal's image is offset when sampled, in viewport UV units, to give it an
+    /// animated ripple or refraction.
+    ///
+    /// This field's value is initialized from [`Portal::distortion`].
+    ///
+    /// Defaults to `0.0`, meaning the image isn't distorted.
+    pub distortion_strength: f32,
+    /// How fast the distortion is animated, as a multiple of the elapsed time.
+    ///
+    /// This field's value is initialized from [`Portal::distortion`].
+    ///
+    /// Defaults to `1.0`.
+    pub distortion_speed: f32,
+    /// A normal map whose `x` and `y` components are used as the distortion offset, scrolling
+    /// across the mesh's UVs over time.
+    ///
+    /// If set to `None`, a procedural ripple is used instead.
+    ///
+    /// This field's value is initialized from [`Portal::distortion`].
+    ///
+    /// Defaults to `None`.
+    #[texture(5)]
+    #[sampler(6)]
+    pub distortion_map: Option<Handle<Image>>,
     /// Specifies which side of the portal to cull: "front", "back", or neither.
     ///
     /// If set to `None`, both sides of the portal’s mesh will be rendered.
     ///
-    /// This field's value is inherited from what is set on [`Portal`], but not kept in sync.
+    /// This field's value is inherited from what is set on [`Portal`], and kept in sync whenever
+    /// [`Portal::cull_mode`] changes.
     ///
     /// Defaults to `Some(Face::Back)`, similar to [`StandardMaterial::cull_mode`] and [`Portal`].
+    #[reflect(remote = crate::reflect::OptionFaceRemote)]
     pub cull_mode: Option<Face>,
     /// The effect of draw calls on the depth and stencil aspects of the portal.
     ///
     /// You can make use of this field to resolve z-fighting.
     ///
     /// Defaults to the standard mesh [`DepthStencilState`].
+    // Remotely reflecting this would require mirroring all of `TextureFormat`
+    #[reflect(ignore)]
     pub depth_stencil: Option<DepthStencilState>,
 }
 
@@ -71,6 +274,14 @@ impl Default for PortalMaterial {
     fn default() -> Self {
         Self {
             base_color_texture: None,
+            tint: LinearRgba::WHITE,
+            edge_fade: 0.0,
+            mask: None,
+            clip_fade: 0.0,
+            uv_transform: Mat3::IDENTITY,
+            distortion_strength: 0.0,
+            distortion_speed: 1.0,
+            distortion_map: None,
             cull_mode: Some(Face::Back),
             depth_stencil: Some(DepthStencilState {
                 format: CORE_3D_DEPTH_FORMAT,
@@ -93,6 +304,14 @@ impl Material for PortalMaterial {
         PORTAL_SHADER_HANDLE.into()
     }
 
+    fn alpha_mode(&self) -> AlphaMode {
+        if self.edge_fade > 0.0 || self.clip_fade > 0.0 || self.mask.is_some() {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        }
+    }
+
     fn specialize(
         _pipeline: &MaterialPipeline<Self>,
         descriptor: &mut RenderPipelineDescriptor,
@@ -101,30 +320,158 @@ impl Material for PortalMaterial {
     ) -> Result<(), SpecializedMeshPipelineError> {
         descriptor.primitive.cull_mode = key.bind_group_data.cull_mode;
         descriptor.depth_stencil = key.bind_group_data.depth_stencil;
+        if let Some(fragment) = descriptor.fragment.as_mut() {
+            match key.bind_group_data.distortion {
+                PortalDistortionKey::None => {}
+                PortalDistortionKey::Ripple => {
+                    fragment.shader_defs.push("PORTAL_DISTORTION".into());
+                }
+                PortalDistortionKey::Map => {
+                    fragment.shader_defs.push("PORTAL_DISTORTION".into());
+                    fragment.shader_defs.push("PORTAL_DISTORTION_MAP".into());
+                }
+            }
+        }
         Ok(())
     }
 }
 
+// The `ShaderType` derive generates layout assertions next to the struct that are never called
+#[allow(dead_code)]
+mod uniform {
+    use bevy::{prelude::*, render::render_resource::ShaderType};
+
+    /// The parameters of a [`PortalMaterial`](super::PortalMaterial), packed into a single uniform
+    /// for its shader.
+    #[derive(Clone, Default, ShaderType)]
+    pub struct PortalMaterialUniform {
+        /// See [`PortalMaterial::tint`](super::PortalMaterial::tint).
+        pub tint: Vec4,
+        /// See [`PortalMaterial::uv_transform`](super::PortalMaterial::uv_transform).
+        pub uv_transform: Mat3,
+        /// See [`PortalMaterial::edge_fade`](super::PortalMaterial::edge_fade).
+        pub edge_fade: f32,
+        /// See [`PortalMaterial::clip_fade`](super::PortalMaterial::clip_fade).
+        pub clip_fade: f32,
+        /// See [`PortalMaterial::distortion_strength`](super::PortalMaterial::distortion_strength).
+        pub distortion_strength: f32,
+        /// See [`PortalMaterial::distortion_speed`](super::PortalMaterial::distortion_speed).
+        pub distortion_speed: f32,
+    }
+}
+
+impl From<&PortalMaterial> for PortalMaterialUniform {
+    fn from(material: &PortalMaterial) -> Self {
+        Self {
+            tint: material.tint.to_vec4(),
+            uv_transform: material.uv_transform,
+            edge_fade: material.edge_fade,
+            clip_fade: material.clip_fade,
+            distortion_strength: material.distortion_strength,
+            distortion_speed: material.distortion_speed,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub struct PortalMaterialKey {
     cull_mode: Option<Face>,
     depth_stencil: Option<DepthStencilState>,
+    distortion: PortalDistortionKey,
+}
+
+/// The kind of distortion applied by a [`PortalMaterial`], used to specialize its pipeline.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum PortalDistortionKey {
+    None,
+    Ripple,
+    Map,
 }
 
 impl From<&PortalMaterial> for PortalMaterialKey {
     fn from(material: &PortalMaterial) -> Self {
+        let distortion = if material.distortion_strength == 0.0 {
+            PortalDistortionKey::None
+        } else if material.distortion_map.is_some() {
+            PortalDistortionKey::Map
+        } else {
+            PortalDistortionKey::Ripple
+        };
         Self {
             cull_mode: material.cull_mode,
             depth_stencil: material.depth_stencil.clone(),
+            distortion,
         }
     }
 }
 
-/// Marks all materials `T` that are on [`Portal`] entities as changed in the asset system.
+/// Settings for an animated distortion of the view through a [`Portal`], such as a ripple or
+/// refraction on its surface.
+///
+/// See [`Portal::distortion`].
+#[derive(Reflect, Debug, Clone)]
+pub struct PortalDistortion {
+    /// See [`PortalMaterial::distortion_strength`].
+    pub strength: f32,
+    /// See [`PortalMaterial::distortion_speed`].
+    pub speed: f32,
+    /// See [`PortalMaterial::distortion_map`].
+    pub normal_map: Option<Handle<Image>>,
+}
+
+impl Default for PortalDistortion {
+    fn default() -> Self {
+        Self {
+            strength: 0.005,
+            speed: 1.0,
+            normal_map: None,
+        }
+    }
+}
+
+impl PortalMaterial {
+    /// Creates a [`PortalMaterial`] rendering the given `image`, usually the [`PortalImage`] of a
+    /// [`Portal`].
+    #[inline]
+    #[must_use]
+    pub fn new(image: Handle<Image>) -> Self {
+        Self {
+            base_color_texture: Some(image),
+            ..default()
+        }
+    }
+
+    /// Creates a [`PortalMaterial`] rendering the given `image` of `portal`, with the settings of
+    /// the portal that are initialized on the material, such as [`Portal::tint`].
+    ///
+    /// This is how the materials of portals (and of their [`PortalRecursionMesh`]es) are created.
+    #[must_use]
+    pub fn from_portal(portal: &Portal, image: Handle<Image>) -> Self {
+        let mut material = Self {
+            base_color_texture: Some(image),
+            tint: portal.tint.map_or(LinearRgba::WHITE, LinearRgba::from),
+            edge_fade: portal.edge_fade,
+            mask: portal.mask.clone(),
+            clip_fade: portal.clip_fade,
+            uv_transform: portal.uv_transform.into(),
+            cull_mode: portal.cull_mode,
+            ..default()
+        };
+        if let Some(distortion) = &portal.distortion {
+            material.distortion_strength = distortion.strength;
+            material.distortion_speed = distortion.speed;
+            material.distortion_map = distortion.normal_map.clone();
+        }
+        material
+    }
+}
+
+/// Marks all materials `T` that are on [`Portal`] entities (or their [`PortalRecursionMesh`]es) as
+/// changed in the asset system.
 ///
 /// See https://github.com/bevyengine/bevy/issues/5069 for context.
 pub fn update_materials<T: Material>(
-    material_query: Query<&MeshMaterial3d<T>, With<Portal>>,
+    material_query: Query<&MeshMaterial3d<T>, Or<(With<Portal>, With<PortalRecursionMesh>)>>,
     mut materials: ResMut<Assets<T>>,
 ) {
     for material_handle in &material_query {
@@ -132,21 +479,256 @@ pub fn update_materials<T: Material>(
     }
 }
 
+/// System that updates the `cull_mode` of the materials of a [`Portal`] (and its
+/// [`PortalRecursionMesh`]es) when [`Portal::cull_mode`] changes.
+///
+/// Since `cull_mode` is part of [`PortalMaterialKey`], mutating the material causes the pipeline to
+/// be re-specialized.
+fn sync_material_cull_modes(
+    portal_query: Query<
+        (&Portal, &MeshMaterial3d<PortalMaterial>, Option<&Children>),
+        Changed<Portal>,
+    >,
+    recursion_mesh_query: Query<
+        (
+            Option<&MeshMaterial3d<PortalMaterial>>,
+            Option<&MeshMaterial3d<StandardMaterial>>,
+        ),
+        With<PortalRecursionMesh>,
+    >,
+    mut portal_materials: ResMut<Assets<PortalMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for (portal, material_handle, children) in &portal_query {
+        let recursion_meshes = children
+            .into_iter()
+            .flatten()
+            .filter_map(|&child| recursion_mesh_query.get(child).ok());
+
+        let portal_material_handles = iter::once(material_handle).chain(
+            recursion_meshes
+                .clone()
+                .filter_map(|(portal_material, _)| portal_material),
+        );
+        for handle in portal_material_handles {
+            if portal_materials
+                .get(handle)
+                .is_some_and(|material| material.cull_mode != portal.cull_mode)
+            {
+                portal_materials.get_mut(handle).unwrap().cull_mode = portal.cull_mode;
+            }
+        }
+
+        for handle in recursion_meshes.filter_map(|(_, standard_material)| standard_material) {
+            if standard_materials
+                .get(handle)
+                .is_some_and(|material| material.cull_mode != portal.cull_mode)
+            {
+                standard_materials.get_mut(handle).unwrap().cull_mode = portal.cull_mode;
+            }
+        }
+    }
+}
+
+/// System that fills the [`PortalImage`] (and [`PortalRecursion::images`]) of disabled [`Portal`]s
+/// with [`Portal::disabled_color`].
+fn fill_disabled_portal_images(
+    portal_query: Query<
+        (
+            &Portal,
+            &PortalImage,
+            &MeshMaterial3d<PortalMaterial>,
+            Option<&PortalRecursion>,
+            Option<&Children>,
+        ),
+        Changed<Portal>,
+    >,
+    recursion_mesh_query: Query<&MeshMaterial3d<PortalMaterial>, With<PortalRecursionMesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<PortalMaterial>>,
+) {
+    for (portal, portal_image, material_handle, recursion, children) in &portal_query {
+        let Some(disabled_color) = portal.disabled_color.filter(|_| !portal.enabled) else {
+            continue;
+        };
+
+        let recursion_images = recursion.iter().flat_map(|recursion| &recursion.images);
+        for image_handle in iter::once(&portal_image.0).chain(recursion_images) {
+            if let Some(image) = images.get_mut(image_handle) {
+                fill_image(image, disabled_color);
+            }
+        }
+
+        // The materials need to be marked as changed to pick up the modified images
+        let recursion_materials = children
+            .iter()
+            .flat_map(|children| recursion_mesh_query.iter_many(children.iter()));
+        for material_handle in iter::once(material_handle).chain(recursion_materials) {
+            materials.get_mut(material_handle);
+        }
+    }
+}
+
+/// Component used to remember which [`PortalMaterial`] was created by [`spawn_material`], so that
+/// it isn't mistaken for a material supplied by the user.
+#[derive(Component, Debug)]
+struct GeneratedPortalMaterial(AssetId<PortalMaterial>);
+
+/// System that inserts a [`PortalMaterial`] on [`Portal`] entities when their [`PortalImage`] is
+/// added, unless they have the material of a [`PortalTextureSink`].
+///
+/// Since the material is only visible on a mesh, a warning is logged if the entity has no
+/// [`Mesh3d`] (and, with the `ui` feature, isn't displayed by a `PortalImageNode` either), or if
+/// [`Portal::clip_fade`] is set without a [`DepthPrepass`] on the primary camera.
 fn spawn_material(
     trigger: Trigger<OnAdd, PortalImage>,
     mut commands: Commands,
-    portal_query: Query<(&Portal, &PortalImage)>,
+    portal_query: Query<(&Portal, &PortalImage, Has<Mesh3d>, EntityRef), Without<Mesh2d>>,
+    #[cfg(feature = "ui")] image_node_query: Query<&crate::ui::PortalImageNode>,
+    camera_query: Query<Has<DepthPrepass>>,
+    texture_sinks: Res<PortalTextureSinks>,
     mut materials: ResMut<Assets<PortalMaterial>>,
 ) {
     let entity = trigger.entity();
-    let Ok((portal, portal_image)) = portal_query.get(entity) else {
+    let Ok((portal, portal_image, has_mesh, entity_ref)) = portal_query.get(entity) else {
         return;
     };
-    commands
-        .entity(entity)
-        .insert(MeshMaterial3d(materials.add(PortalMaterial {
-            base_color_texture: Some(portal_image.0.clone()),
-            cull_mode: portal.cull_mode,
-            ..default()
-        })));
+    if texture_sinks
+        .0
+        .iter()
+        .any(|&component_id| entity_ref.contains_id(component_id))
+    {
+        return;
+    }
+    #[cfg(feature = "ui")]
+    let has_mesh = has_mesh || image_node_query.iter().any(|node| node.portal == entity);
+    if !has_mesh {
+        warn!(
+            "portal {entity} has no Mesh3d, so it won't be visible; add a mesh (e.g. a Rectangle) \
+            to the portal entity for its PortalMaterial to be rendered"
+        );
+    }
+    if portal.clip_fade > 0.0 && camera_query.get(portal.primary_camera) == Ok(false) {
+        warn!(
+            "portal {entity} has a clip_fade, but its primary camera has no DepthPrepass, so \
+            objects will still be cut off sharply at the portal's plane"
+        );
+    }
+
+    let material_handle =
+        materials.add(PortalMaterial::from_portal(portal, portal_image.0.clone()));
+    commands.entity(entity).insert((
+        GeneratedPortalMaterial(material_handle.id()),
+        MeshMaterial3d(material_handle),
+    ));
+}
+
+/// System that removes the material inserted by [`spawn_material`] when the [`Portal`] component
+/// is removed from a triggered entity.
+///
+/// If the [`MeshMaterial3d<PortalMaterial>`] has since been replaced by the user, it is left as is.
+fn despawn_material(
+    trigger: Trigger<OnRemove, Portal>,
+    mut commands: Commands,
+    material_query: Query<(
+        &GeneratedPortalMaterial,
+        Option<&MeshMaterial3d<PortalMaterial>>,
+    )>,
+) {
+    let entity = trigger.entity();
+    let Ok((generated_material, material_handle)) = material_query.get(entity) else {
+        return;
+    };
+
+    let mut entity_commands = commands.entity(entity);
+    entity_commands.remove::<GeneratedPortalMaterial>();
+    if material_handle.is_some_and(|handle| handle.id() == generated_material.0) {
+        entity_commands.remove::<MeshMaterial3d<PortalMaterial>>();
+    }
+}
+
+/// Component used to mark the meshes spawned to render a [`Portal`] recursively.
+///
+/// See [`Portal::recursion_depth`] for more details.
+#[derive(Component, Reflect, Debug)]
+#[reflect(Component)]
+pub struct PortalRecursionMesh;
+
+/// System that spawns a mesh for each level of a [`Portal`]'s recursion, as children of the portal.
+///
+/// Each mesh renders the image of its level, and is only visible to the camera of the level before
+/// it. The mesh of the innermost level is rendered using the primary camera's clear color, or
+/// [`Portal::clear_color`] if set.
+fn spawn_recursion_meshes(
+    trigger: Trigger<OnAdd, PortalRecursion>,
+    mut commands: Commands,
+    portal_query: Query<(&Portal, &PortalRecursion, &Mesh3d)>,
+    camera_query: Query<&Camera>,
+    clear_color: Res<ClearColor>,
+    mut portal_materials: ResMut<Assets<PortalMaterial>>,
+    mut standard_materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let entity = trigger.entity();
+    let Ok((portal, recursion, mesh)) = portal_query.get(entity) else {
+        return;
+    };
+
+    let clear_color_config = portal.clear_color.as_ref().or_else(|| {
+        camera_query
+            .get(portal.primary_camera)
+            .ok()
+            .map(|camera| &camera.clear_color)
+    });
+    let fallback_color = match clear_color_config {
+        Some(ClearColorConfig::Custom(color)) => *color,
+        Some(ClearColorConfig::None) => Color::BLACK,
+        _ => clear_color.0,
+    };
+
+    commands.entity(entity).with_children(|parent| {
+        for (level, image) in recursion.images.iter().enumerate() {
+            parent.spawn((
+                Name::new("Portal Recursion Mesh"),
+                mesh.clone(),
+                MeshMaterial3d(
+                    portal_materials.add(PortalMaterial::from_portal(portal, image.clone())),
+                ),
+                RenderLayers::layer(PORTAL_RECURSION_LAYER + level + 1),
+                PortalRecursionMesh,
+            ));
+        }
+
+        // Anything beyond the last level of recursion can't be rendered
+        parent.spawn((
+            Name::new("Portal Recursion Mesh"),
+            mesh.clone(),
+            MeshMaterial3d(standard_materials.add(StandardMaterial {
+                base_color: fallback_color,
+                unlit: true,
+                cull_mode: portal.cull_mode,
+                ..default()
+            })),
+            RenderLayers::layer(PORTAL_RECURSION_LAYER + recursion.images.len() + 1),
+            PortalRecursionMesh,
+        ));
+    });
+}
+
+/// System that despawns the meshes spawned by [`spawn_recursion_meshes`] when the [`Portal`]
+/// component is removed from a triggered entity.
+fn despawn_recursion_meshes(
+    trigger: Trigger<OnRemove, Portal>,
+    mut commands: Commands,
+    children_query: Query<&Children>,
+    recursion_mesh_query: Query<(), With<PortalRecursionMesh>>,
+) {
+    let Ok(children) = children_query.get(trigger.entity()) else {
+        return;
+    };
+
+    for &child in children {
+        if recursion_mesh_query.contains(child) {
+            commands.entity(child).try_despawn_recursive();
+        }
+    }
 }
@@ -0,0 +1,85 @@
+//! Materials for 2D [`Portal`]s.
+//!
+//! A [`Portal`] is 2D if its [`Portal::primary_camera`] is a [`Camera2d`]. Rather than a
+//! [`Mesh3d`], the entity containing the [`Portal`] should have a [`Mesh2d`], which is given a
+//! [`PortalMaterial2d`] automatically.
+
+use bevy::{
+    asset::load_internal_asset,
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, Material2dPlugin},
+};
+
+use crate::{
+    camera::{PortalCameraSystems, PortalImage, PortalImagesResized},
+    Portal,
+};
+
+pub const PORTAL_2D_SHADER_HANDLE: Handle<Shader> =
+    Handle::weak_from_u128(250457163497291542466349317092716373418);
+
+pub struct PortalMaterial2dPlugin;
+
+impl Plugin for PortalMaterial2dPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(
+            app,
+            PORTAL_2D_SHADER_HANDLE,
+            concat!(env!("CARGO_MANIFEST_DIR"), "/assets/portal_2d.wgsl"),
+            Shader::from_wgsl
+        );
+
+        app.add_plugins(Material2dPlugin::<PortalMaterial2d>::default())
+            .add_systems(
+                PreUpdate,
+                update_materials_2d::<PortalMaterial2d>
+                    .run_if(on_event::<PortalImagesResized>)
+                    .after(PortalCameraSystems::ResizeImage),
+            )
+            .add_observer(spawn_material_2d);
+    }
+}
+
+/// Material used for a 2D [`Portal`]'s mesh.
+#[derive(Asset, AsBindGroup, Clone, Reflect, Default)]
+pub struct PortalMaterial2d {
+    #[texture(0)]
+    #[sampler(1)]
+    base_color_texture: Option<Handle<Image>>,
+}
+
+impl Material2d for PortalMaterial2d {
+    fn fragment_shader() -> ShaderRef {
+        PORTAL_2D_SHADER_HANDLE.into()
+    }
+}
+
+/// Marks all materials `T` that are on [`Portal`] entities as changed in the asset system.
+///
+/// This is the 2D equivalent of [`update_materials`](crate::material::update_materials).
+pub fn update_materials_2d<T: Material2d>(
+    material_query: Query<&MeshMaterial2d<T>, With<Portal>>,
+    mut materials: ResMut<Assets<T>>,
+) {
+    for material_handle in &material_query {
+        materials.get_mut(material_handle);
+    }
+}
+
+fn spawn_material_2d(
+    trigger: Trigger<OnAdd, PortalImage>,
+    mut commands: Commands,
+    portal_query: Query<&PortalImage, (With<Portal>, With<Mesh2d>)>,
+    mut materials: ResMut<Assets<PortalMaterial2d>>,
+) {
+    let entity = trigger.entity();
+    let Ok(portal_image) = portal_query.get(entity) else {
+        return;
+    };
+    commands
+        .entity(entity)
+        .insert(MeshMaterial2d(materials.add(PortalMaterial2d {
+            base_color_texture: Some(portal_image.0.clone()),
+        })));
+}
@@ -4,7 +4,9 @@
 //!
 //! This module does *not* provide any backend for you. It provides custom inputs that are
 //! compatible with any backend. The entity containing the [`Portal`] will need to be picked via a
-//! backend, hits will then be sent "through" the target.
+//! backend, hits will then be sent "through" the target. Pointers are only created for a portal
+//! once it's hovered (see [`PortalPointer`]), so portals spawned before the plugin is added work
+//! too.
 //!
 //! Some backends support opt-in behavior for picking, where cameras and entities require a marker
 //! component to be considered in the backend. This also applies to portal cameras.
@@ -12,12 +14,17 @@
 use bevy::{
     picking::{
         focus::HoverMap,
-        pointer::{Location, PointerAction, PointerId, PointerInput, PointerLocation},
+        mesh_picking::ray_cast::{ray_mesh_intersection, Backfaces},
+        pointer::{Location, PointerAction, PointerId, PointerInput},
         PickSet,
     },
     prelude::*,
-    render::camera::NormalizedRenderTarget,
-    utils::HashSet,
+    render::{
+        camera::NormalizedRenderTarget,
+        mesh::{Indices, PrimitiveTopology},
+        primitives::Aabb,
+    },
+    utils::{HashMap, HashSet},
 };
 use uuid::Uuid;
 
@@ -36,10 +43,35 @@ impl Plugin for PortalPickingPlugin {
                     portal_picking.in_set(PickSet::Last),
                 ),
             )
-            .add_observer(add_pointer);
+            .register_type::<PortalPointer>();
     }
 }
 
+/// Component on a [`Portal`] that stores the last ray cast through it for picking, along with
+/// where it hit the portal.
+///
+/// Removed once the portal is no longer hovered or dragged. This is mainly useful for debugging,
+/// e.g. through `PortalGizmos::draw_picking` if the `gizmos` feature is enabled.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PortalPickingRay {
+    /// The ray cast from [`Portal::primary_camera`], in world space.
+    pub ray: Ray3d,
+    /// The point at which `ray` hit the portal's mesh (or plane), in world space.
+    pub point: Vec3,
+}
+
+/// Component on a pointer that forwards the inputs of another pointer "through" a [`Portal`].
+///
+/// One of these is spawned as a child of a portal for every pointer that hovers or drags it, so
+/// that several pointers (e.g. multiple touches) interact through the same portal independently.
+/// It is despawned once its [`PortalPointer::source`] no longer hovers or drags the portal.
+#[derive(Component, Reflect, Debug, Clone, Copy)]
+#[reflect(Component, Debug)]
+pub struct PortalPointer {
+    /// The pointer whose inputs are forwarded.
+    pub source: PointerId,
+}
+
 /// Used to send inputs obtained in [`portal_picking`] in the next frame.
 #[derive(Event, Debug)]
 struct PortalInput {
@@ -48,25 +80,6 @@ struct PortalInput {
     action: PointerAction,
 }
 
-/// Adds [`PointerId`] and [`PointerLocation`] to entities that have a [`PortalImage`] added.
-fn add_pointer(
-    trigger: Trigger<OnAdd, PortalImage>,
-    mut commands: Commands,
-    query: Query<(Entity, &PortalImage)>,
-) {
-    let (entity, portal_image) = query.get(trigger.entity()).unwrap();
-
-    let location = Location {
-        target: NormalizedRenderTarget::Image(portal_image.0.clone()),
-        position: Vec2::ZERO,
-    };
-
-    commands.entity(entity).insert((
-        PointerId::Custom(Uuid::new_v4()),
-        PointerLocation::new(location),
-    ));
-}
-
 /// Maps incoming [`PortalInput`]s to [`PointerInput`]s.
 fn portal_inputs(
     mut portal_inputs: EventReader<PortalInput>,
@@ -83,12 +96,32 @@ fn portal_inputs(
 
 /// Handles picking.
 ///
-/// To allow for the [`PointerLocation`] to not lag behind, we raycast against the portal's normal.
-/// This comes at the cost of a single frame hit delay.
+/// To allow for the [`PointerLocation`](bevy::picking::pointer::PointerLocation) to not lag behind, we raycast against the portal's mesh,
+/// falling back to the portal's plane if the mesh isn't available. This comes at the cost of a
+/// single frame hit delay.
+///
+/// Movement is only forwarded while the pointer is over the portal's mesh, and maps to a position
+/// within the portal's image. Once a dragging pointer leaves the portal, its presses and releases
+/// are still forwarded (clamped to the image), so that the drag on the other side can end.
+///
+/// Each pointer is forwarded through its own [`PortalPointer`], which is spawned once the pointer
+/// hovers or drags a portal. Portal pointers (and [`PortalPickingRay`]s) of portals that are no
+/// longer hovered or dragged are despawned (or removed), so that nothing remains hovered behind
+/// them.
 fn portal_picking(
-    portal_query: Query<(&Portal, &Transform, &PointerId, &PointerLocation)>,
+    mut commands: Commands,
+    portal_query: Query<(
+        &Portal,
+        &GlobalTransform,
+        &PortalImage,
+        Option<&Mesh3d>,
+        Option<&Aabb>,
+    )>,
+    portal_pointer_query: Query<(Entity, &Parent, &PortalPointer, &PointerId)>,
+    picking_ray_query: Query<Entity, (With<Portal>, With<PortalPickingRay>)>,
     camera_global_transform_query: Query<(&Camera, &GlobalTransform)>,
     camera_query: Query<&Camera>,
+    meshes: Res<Assets<Mesh>>,
     hover_map: Res<HoverMap>,
     pointer_state: Res<PointerState>,
     mut pointer_inputs: EventReader<PointerInput>,
@@ -118,8 +151,27 @@ fn portal_picking(
         }
     }
 
+    let mut portal_pointers = HashMap::new();
+    for (pointer_entity, parent, portal_pointer, &portal_pointer_id) in &portal_pointer_query {
+        let key = (portal_pointer.source, parent.get());
+        if portals.contains(&key) {
+            portal_pointers.insert(key, portal_pointer_id);
+        } else {
+            commands.entity(pointer_entity).despawn_recursive();
+        }
+    }
+
+    for entity in &picking_ray_query {
+        if !portals.iter().any(|&(_, portal)| portal == entity) {
+            commands.entity(entity).remove::<PortalPickingRay>();
+        }
+    }
+
+    // Every portal may be visited once for each pointer, so read the inputs up front
+    let pointer_inputs: Vec<_> = pointer_inputs.read().collect();
+
     for (pointer_id, entity) in portals {
-        let Ok((portal, &portal_transform, &portal_pointer_id, portal_pointer_location)) =
+        let Ok((portal, portal_transform, portal_image, portal_mesh, portal_aabb)) =
             portal_query.get(entity)
         else {
             // This could fail because we store entities from the previous frame in
@@ -139,12 +191,20 @@ fn portal_picking(
         else {
             continue;
         };
-        // TODO: Having `target` cached here is nice, but shouldn't `PointerLocation::Location` be
-        // set to `None` if the portal isn't being hovered?
-        let target = portal_pointer_location.location().cloned().unwrap().target;
+        let target = NormalizedRenderTarget::Image(portal_image.0.clone());
+        // The pointer is spawned with commands, but inputs are only sent for it in the next frame
+        let portal_pointer_id = *portal_pointers
+            .entry((pointer_id, entity))
+            .or_insert_with(|| {
+                let portal_pointer_id = PointerId::Custom(Uuid::new_v4());
+                commands
+                    .entity(entity)
+                    .with_child((PortalPointer { source: pointer_id }, portal_pointer_id));
+                portal_pointer_id
+            });
 
         for input in pointer_inputs
-            .read()
+            .iter()
             .filter(|input| input.pointer_id == pointer_id)
         {
             // Manually retrieve the current pointer's position, so that it doesn't lag a frame
@@ -156,20 +216,54 @@ fn portal_picking(
             else {
                 continue;
             };
-            // Get the distance from the ray's origin to the portal's normal
-            let Some(distance) = ray.intersect_plane(
-                portal_transform.translation,
-                InfinitePlane3d::new(portal_transform.forward()),
-            ) else {
+            let is_move = matches!(input.action, PointerAction::Moved { .. });
+            // Get the distance from the ray's origin to the portal's mesh, or its plane
+            let mesh = portal_mesh
+                .and_then(|mesh| meshes.get(mesh))
+                .filter(|mesh| mesh.primitive_topology() == PrimitiveTopology::TriangleList);
+            let mesh_distance =
+                mesh.and_then(|mesh| ray_mesh_distance(ray, mesh, portal_transform));
+            // Once the pointer has left the portal's mesh (e.g. while dragging), its movement no
+            // longer maps to anything on the other side. Other actions are still forwarded, so that
+            // a drag can end
+            if is_move && mesh.is_some() && mesh_distance.is_none() {
+                continue;
+            }
+            let Some(distance) = mesh_distance.or_else(|| {
+                let plane_transform = portal.plane_transform(portal_transform, portal_aabb);
+                ray.intersect_plane(
+                    plane_transform.translation(),
+                    Portal::plane(&plane_transform),
+                )
+            }) else {
                 continue;
             };
             // We can get the world position of the intersection now. Finally, we use it and
             // convert to the portal camera's viewport
-            let Ok(position) =
-                portal_camera.world_to_viewport(primary_camera_transform, ray.get_point(distance))
-            else {
+            let point = ray.get_point(distance);
+            commands
+                .entity(entity)
+                .insert(PortalPickingRay { ray, point });
+            // The point lies on the portal's plane, which is where the portal camera's oblique near
+            // plane is. Its depth is therefore ignored, unlike in `Camera::world_to_viewport`
+            let (Some(ndc), Some(viewport_size)) = (
+                portal_camera.world_to_ndc(primary_camera_transform, point),
+                portal_camera.logical_viewport_size(),
+            ) else {
                 continue;
             };
+            // The position may fall outside of the portal's image, e.g. if the ray only grazes the
+            // portal's plane
+            let ndc = ndc.truncate();
+            let ndc = if ndc.abs().cmple(Vec2::ONE).all() {
+                ndc
+            } else if is_move {
+                continue;
+            } else {
+                ndc.clamp(Vec2::NEG_ONE, Vec2::ONE)
+            };
+            // Flip the `y` origin from the bottom to the top
+            let position = (ndc * Vec2::new(1.0, -1.0) + Vec2::ONE) / 2.0 * viewport_size;
 
             // We could use `Commands::send_event` here, but I'm not sure if it will hurt
             // performance
@@ -184,3 +278,39 @@ fn portal_picking(
         }
     }
 }
+
+/// Returns the distance from the origin of `ray` to the closest intersection with `mesh`, if any.
+///
+/// `mesh` is expected to have a [`PrimitiveTopology::TriangleList`] topology.
+fn ray_mesh_distance(ray: Ray3d, mesh: &Mesh, transform: &GlobalTransform) -> Option<f32> {
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let transform = transform.compute_matrix();
+
+    let hit = match mesh.indices() {
+        Some(Indices::U16(indices)) => ray_mesh_intersection(
+            ray,
+            &transform,
+            positions,
+            None,
+            Some(indices),
+            Backfaces::Include,
+        ),
+        Some(Indices::U32(indices)) => ray_mesh_intersection(
+            ray,
+            &transform,
+            positions,
+            None,
+            Some(indices),
+            Backfaces::Include,
+        ),
+        None => ray_mesh_intersection::<usize>(
+            ray,
+            &transform,
+            positions,
+            None,
+            None,
+            Backfaces::Include,
+        ),
+    };
+    hit.map(|hit| hit.distance)
+}
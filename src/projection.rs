@@ -0,0 +1,155 @@
+//! Projection used by [`PortalCamera`]s.
+//!
+//! [`PortalCamera`]: crate::camera::PortalCamera
+
+use bevy::{
+    math::Vec3A,
+    prelude::*,
+    render::camera::{CameraProjection, SubCameraView},
+};
+
+/// A [`CameraProjection`] that wraps a [`Projection`], optionally replacing its near plane with an
+/// arbitrary (oblique) clipping plane.
+///
+/// [`PortalCamera`]s use this to clip any geometry that lies between the camera and the plane of
+/// [`Portal::target`], which would otherwise be rendered "in front" of the portal.
+///
+/// See <https://terathon.com/lengyel/Lengyel-Oblique.pdf> for details on the technique.
+///
+/// The near plane is applied to the matrix returned by [`CameraProjection::get_clip_from_view`]
+/// of the wrapped projection, and the camera's [`Frustum`] is derived from the resulting matrix, so
+/// no particular frustum layout is assumed. Custom projections can't be wrapped though, since
+/// [`Projection`] only supports perspective and orthographic projections.
+///
+/// [`Frustum`]: bevy::render::primitives::Frustum
+/// [`PortalCamera`]: crate::camera::PortalCamera
+/// [`Portal::target`]: crate::Portal::target
+#[derive(Component, Reflect, Debug, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct PortalProjection {
+    /// The projection inherited from [`Portal::primary_camera`].
+    ///
+    /// [`Portal::primary_camera`]: crate::Portal::primary_camera
+    pub projection: Projection,
+    /// The clipping plane to use as the near plane, in view space.
+    ///
+    /// The `xyz` components are the plane's normal, pointing towards the visible side, and `w` is
+    /// the plane's signed distance from the origin.
+    ///
+    /// If set to `None`, the near plane of [`PortalProjection::projection`] is used.
+    pub near_plane: Option<Vec4>,
+    /// The size of [`Portal::primary_camera`]'s viewport, used in place of the size of the portal
+    /// camera's render target when updating [`PortalProjection::projection`].
+    ///
+    /// Portals sample their image in screen space, so the view must keep the aspect ratio of the
+    /// primary camera's viewport, even if the image has a different one (see
+    /// [`Portal::fixed_resolution`]).
+    ///
+    /// If set to `None`, the size of the render target is used.
+    ///
+    /// [`Portal::primary_camera`]: crate::Portal::primary_camera
+    /// [`Portal::fixed_resolution`]: crate::Portal::fixed_resolution
+    pub viewport_size: Option<Vec2>,
+}
+
+impl PortalProjection {
+    /// Creates a new [`PortalProjection`] from a given `projection`, without an oblique near plane.
+    #[inline]
+    #[must_use]
+    pub fn new(projection: Projection) -> Self {
+        Self {
+            projection,
+            near_plane: None,
+            viewport_size: None,
+        }
+    }
+
+    /// Sets the far plane distance of [`PortalProjection::projection`].
+    pub fn set_far(&mut self, far: f32) {
+        match &mut self.projection {
+            Projection::Perspective(perspective) => perspective.far = far,
+            Projection::Orthographic(orthographic) => orthographic.far = far,
+        }
+    }
+
+    /// Applies [`PortalProjection::near_plane`] to `clip_from_view`, if set.
+    fn apply_near_plane(&self, clip_from_view: Mat4) -> Mat4 {
+        self.near_plane
+            .and_then(|near_plane| oblique_clip_from_view(clip_from_view, near_plane))
+            .unwrap_or(clip_from_view)
+    }
+}
+
+impl From<Projection> for PortalProjection {
+    fn from(projection: Projection) -> Self {
+        Self::new(projection)
+    }
+}
+
+impl CameraProjection for PortalProjection {
+    fn get_clip_from_view(&self) -> Mat4 {
+        self.apply_near_plane(self.projection.get_clip_from_view())
+    }
+
+    fn get_clip_from_view_for_sub(&self, sub_view: &SubCameraView) -> Mat4 {
+        self.apply_near_plane(self.projection.get_clip_from_view_for_sub(sub_view))
+    }
+
+    fn update(&mut self, width: f32, height: f32) {
+        let (width, height) = self
+            .viewport_size
+            .map_or((width, height), |viewport_size| viewport_size.into());
+        self.projection.update(width, height);
+    }
+
+    fn far(&self) -> f32 {
+        self.projection.far()
+    }
+
+    fn get_frustum_corners(&self, z_near: f32, z_far: f32) -> [Vec3A; 8] {
+        self.projection.get_frustum_corners(z_near, z_far)
+    }
+}
+
+/// Modifies `clip_from_view` such that its near plane coincides with `near_plane` (in view space).
+///
+/// Bevy uses a reversed depth buffer, so the near plane corresponds to a depth of `1` and the far
+/// plane to a depth of `0`. The far plane is adjusted to pass through the corner of the view
+/// frustum opposite the near plane, which minimizes the loss of depth precision.
+///
+/// Returns `None` if the plane can't be used as a near plane, e.g. if it's parallel to the view
+/// direction or the camera is on its visible side.
+fn oblique_clip_from_view(clip_from_view: Mat4, near_plane: Vec4) -> Option<Mat4> {
+    // The camera must be behind the plane, otherwise geometry in front of the camera would be
+    // clipped
+    if near_plane.w >= 0.0 {
+        return None;
+    }
+
+    let view_from_clip = clip_from_view.inverse();
+    let clip_near_plane = view_from_clip.transpose() * near_plane;
+    // The far corner of the frustum opposite to the plane
+    let corner = view_from_clip
+        * Vec4::new(
+            clip_near_plane.x.signum(),
+            clip_near_plane.y.signum(),
+            0.0,
+            1.0,
+        );
+
+    let row_w = clip_from_view.row(3);
+    let scale = row_w.dot(corner) / near_plane.dot(corner);
+    if !scale.is_finite() || scale <= 0.0 {
+        return None;
+    }
+
+    // The near plane is described by `row_w - row_z`, so the new depth row follows
+    let row_z = row_w - near_plane * scale;
+    let mut clip_from_view = clip_from_view;
+    clip_from_view.x_axis.z = row_z.x;
+    clip_from_view.y_axis.z = row_z.y;
+    clip_from_view.z_axis.z = row_z.z;
+    clip_from_view.w_axis.z = row_z.w;
+
+    Some(clip_from_view)
+}
@@ -0,0 +1,19 @@
+//! Remote reflection for types that don't implement [`Reflect`] upstream.
+
+use bevy::{reflect::reflect_remote, render::render_resource::Face};
+
+/// Remote reflection of [`Face`].
+#[reflect_remote(Face)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum FaceRemote {
+    Front,
+    Back,
+}
+
+/// Remote reflection of an optional [`Face`], as used for cull modes.
+#[reflect_remote(Option<Face>)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum OptionFaceRemote {
+    None,
+    Some(#[reflect(remote = FaceRemote)] Face),
+}
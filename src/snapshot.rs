@@ -0,0 +1,120 @@
+//! Reading back the rendered image of a [`Portal`] to the CPU.
+//!
+//! Trigger [`RequestPortalSnapshot`] on a portal entity, and observe [`PortalSnapshot`] on the same
+//! entity to receive a copy of its image once the GPU has finished reading it back:
+//!
+//! ```
+//! # use bevy::prelude::*;
+//! # use bevy_easy_portals::snapshot::{PortalSnapshot, RequestPortalSnapshot};
+//! fn take_snapshot(mut commands: Commands, portal: Entity) {
+//!     commands
+//!         .entity(portal)
+//!         .observe(|trigger: Trigger<PortalSnapshot>| {
+//!             info!("portal image is {:?}", trigger.event().0.size());
+//!         });
+//!     commands.trigger_targets(RequestPortalSnapshot, portal);
+//! }
+//! ```
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::TextureFormatPixelInfo,
+    prelude::*,
+    render::{
+        gpu_readback::{Readback, ReadbackComplete},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        renderer::RenderDevice,
+    },
+};
+
+use crate::{camera::PortalImage, Portal};
+
+/// Plugin that provides [`Portal`] snapshots through [`RequestPortalSnapshot`].
+pub struct PortalSnapshotPlugin;
+
+impl Plugin for PortalSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(request_snapshot);
+    }
+}
+
+/// Event that requests a [`PortalSnapshot`] of the [`Portal`] entity it is triggered on.
+#[derive(Event, Debug, Default, Clone, Copy)]
+pub struct RequestPortalSnapshot;
+
+/// Event triggered on a [`Portal`] entity with a copy of its rendered [`PortalImage`], in response
+/// to a [`RequestPortalSnapshot`].
+///
+/// The image is read back a few frames after the request, and reflects what the portal had
+/// rendered at that point.
+#[derive(Event, Debug)]
+pub struct PortalSnapshot(pub Image);
+
+/// System that starts a GPU readback of a [`Portal`]'s [`PortalImage`] when
+/// [`RequestPortalSnapshot`] is triggered on it.
+///
+/// The readback entity triggers [`PortalSnapshot`] on the portal once complete, and then despawns
+/// itself.
+fn request_snapshot(
+    trigger: Trigger<RequestPortalSnapshot>,
+    mut commands: Commands,
+    portal_query: Query<&PortalImage, With<Portal>>,
+) {
+    let portal = trigger.entity();
+    let Ok(portal_image) = portal_query.get(portal) else {
+        warn!("could not take a snapshot of {portal}: it isn't a portal with an image");
+        return;
+    };
+
+    let image_handle = portal_image.0.clone_weak();
+    commands
+        .spawn((
+            Name::new("Portal Snapshot"),
+            Readback::texture(image_handle.clone()),
+        ))
+        .observe(
+            move |trigger: Trigger<ReadbackComplete>,
+                  mut commands: Commands,
+                  images: Res<Assets<Image>>| {
+                commands.entity(trigger.entity()).despawn();
+
+                let Some(image) = images.get(&image_handle) else {
+                    return;
+                };
+                let descriptor = &image.texture_descriptor;
+                match unpad_image(&trigger.event().0, descriptor.size, descriptor.format) {
+                    Some(snapshot) => {
+                        commands.trigger_targets(PortalSnapshot(snapshot), portal);
+                    }
+                    None => warn!("could not take a snapshot of {portal}: the image was resized"),
+                }
+            },
+        );
+}
+
+/// Creates an [`Image`] from the data of a texture readback, removing the padding that is added
+/// to each row.
+///
+/// Returns `None` if `data` doesn't match the given `size` and `format`.
+fn unpad_image(data: &[u8], size: Extent3d, format: TextureFormat) -> Option<Image> {
+    let height = size.height as usize;
+    let row_size = size.width as usize * format.pixel_size();
+    let padded_row_size = RenderDevice::align_copy_bytes_per_row(row_size);
+    if height == 0 || data.len() < padded_row_size * height {
+        return None;
+    }
+
+    let data = data
+        .chunks_exact(padded_row_size)
+        .take(height)
+        .flat_map(|row| &row[..row_size])
+        .copied()
+        .collect();
+    Some(Image::new(
+        size,
+        TextureDimension::D2,
+        data,
+        format,
+        RenderAssetUsages::default(),
+    ))
+}
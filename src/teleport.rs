@@ -0,0 +1,177 @@
+//! Teleportation through portals.
+//!
+//! Add the [`PortalTeleportPlugin`] and mark entities with [`Teleportable`] to have them teleported
+//! to a [`Portal::target`] whenever they pass through the [`Portal`]'s mesh.
+//!
+//! Teleportation is based on the [`Transform`] of an entity, so [`Teleportable`] entities should not
+//! have a [`Parent`]. To react to a teleport (e.g. to reset or rotate a velocity), read the
+//! [`PortalTeleport`] event. Since this crate doesn't depend on a physics engine, velocities aren't
+//! remapped automatically; use [`PortalTeleport::remap_linear_velocity`] and
+//! [`PortalTeleport::remap_angular_velocity`] to apply the teleport to your own velocity
+//! components.
+//!
+//! Teleportation happens in [`PortalCameraSystems::Teleport`]. Systems that react to teleports in
+//! the same frame should run after it, and any custom teleportation logic should run in it.
+
+use bevy::{
+    prelude::*,
+    render::{primitives::Aabb, render_resource::Face},
+};
+
+use crate::{
+    camera::{remap_transform, uniform_scale, PortalCameraSystems},
+    Portal,
+};
+
+/// Enables teleporting [`Teleportable`] entities through [`Portal`]s.
+pub struct PortalTeleportPlugin;
+
+impl Plugin for PortalTeleportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PortalTeleport>()
+            .add_systems(
+                PostUpdate,
+                teleport_entities.in_set(PortalCameraSystems::Teleport),
+            )
+            .register_type::<Teleportable>();
+    }
+}
+
+/// Component used to mark entities that should be teleported when passing through a [`Portal`].
+#[derive(Component, Reflect, Debug, Default)]
+#[reflect(Component, Default)]
+#[require(Transform, PreviousTranslation)]
+pub struct Teleportable;
+
+/// Event sent when a [`Teleportable`] entity is teleported through a [`Portal`].
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PortalTeleport {
+    /// The entity that was teleported.
+    pub entity: Entity,
+    /// The entity containing the [`Portal`] that was passed through.
+    pub portal: Entity,
+    /// The [`Transform`] of the entity before being teleported.
+    pub from: Transform,
+    /// The [`Transform`] of the entity after being teleported.
+    pub to: Transform,
+    /// The rotation applied to the entity by the teleport, in world space.
+    ///
+    /// This is the rotation from the [`Portal`] to its target, such that
+    /// `to.rotation == rotation * from.rotation`.
+    pub rotation: Quat,
+    /// The ratio between the scale of the [`Portal`]'s target and the scale of the [`Portal`].
+    ///
+    /// This is `1.0` unless the portal is "bigger on the inside" (or smaller).
+    pub scale: f32,
+}
+
+impl PortalTeleport {
+    /// Remaps a world space linear `velocity` of the teleported entity, such that it keeps moving
+    /// in the same direction relative to the portal it exited from.
+    #[inline]
+    pub fn remap_linear_velocity(&self, velocity: Vec3) -> Vec3 {
+        self.rotation * velocity * self.scale
+    }
+
+    /// Remaps a world space angular `velocity` of the teleported entity, such that it keeps
+    /// rotating around the same axis relative to the portal it exited from.
+    #[inline]
+    pub fn remap_angular_velocity(&self, velocity: Vec3) -> Vec3 {
+        self.rotation * velocity
+    }
+}
+
+/// Stores the translation of a [`Teleportable`] entity from the previous frame.
+#[derive(Component, Debug, Default)]
+struct PreviousTranslation(Option<Vec3>);
+
+/// System that teleports [`Teleportable`] entities that have passed through a [`Portal`] since the
+/// previous frame.
+///
+/// An entity has passed through a portal if the line between its previous and current translation
+/// crosses the portal's plane (see [`Portal::plane_transform`]) within the bounds of the portal's
+/// mesh. This means entities that enter and exit the portal's bounds on the same side are not
+/// teleported. If multiple portals are passed through, the entity is teleported through the first
+/// one it reached.
+///
+/// Passing through a culled face of the portal doesn't teleport the entity.
+fn teleport_entities(
+    mut teleportable_query: Query<
+        (Entity, &mut Transform, &mut PreviousTranslation),
+        (With<Teleportable>, Without<Parent>),
+    >,
+    portal_query: Query<(Entity, &Portal, &GlobalTransform, &Aabb)>,
+    global_transform_query: Query<&GlobalTransform>,
+    mut teleports: EventWriter<PortalTeleport>,
+) {
+    for (entity, mut transform, mut previous_translation) in &mut teleportable_query {
+        let Some(start) = previous_translation.0.replace(transform.translation) else {
+            continue;
+        };
+        let end = transform.translation;
+        if start == end {
+            continue;
+        }
+
+        // Find the first portal passed through, along with how far along the line it was reached
+        let mut closest: Option<(f32, Entity, &Portal, &GlobalTransform)> = None;
+
+        for (portal_entity, portal, portal_transform, aabb) in &portal_query {
+            // Work in the local space of the portal's plane, where its normal is along the z-axis
+            let world_to_local = portal
+                .plane_transform(portal_transform, Some(aabb))
+                .affine()
+                .inverse();
+            let local_start = world_to_local.transform_point3(start);
+            let local_end = world_to_local.transform_point3(end);
+
+            let starts_in_front = local_start.z > 0.0;
+            if starts_in_front == (local_end.z > 0.0) {
+                continue;
+            }
+
+            // The front face of the portal faces its back direction (+Z)
+            let culled = match portal.cull_mode {
+                Some(Face::Back) => !starts_in_front,
+                Some(Face::Front) => starts_in_front,
+                None => false,
+            };
+            if culled {
+                continue;
+            }
+
+            let t = local_start.z / (local_start.z - local_end.z);
+            let hit = local_start.lerp(local_end, t);
+            let offset = (hit - Vec3::from(aabb.center)).abs();
+            if offset.x > aabb.half_extents.x || offset.y > aabb.half_extents.y {
+                continue;
+            }
+
+            if closest.is_none_or(|(closest_t, ..)| t < closest_t) {
+                closest = Some((t, portal_entity, portal, portal_transform));
+            }
+        }
+
+        let Some((_, portal_entity, portal, portal_transform)) = closest else {
+            continue;
+        };
+        let Some(target_transform) = portal.get_target_transform(&global_transform_query) else {
+            continue;
+        };
+
+        let from = *transform;
+        *transform = remap_transform(portal_transform, &target_transform, &from);
+        // Avoid detecting a crossing between the entity's old and new translation
+        previous_translation.0 = Some(transform.translation);
+
+        teleports.send(PortalTeleport {
+            entity,
+            portal: portal_entity,
+            from,
+            to: *transform,
+            rotation: transform.rotation * from.rotation.inverse(),
+            scale: uniform_scale(target_transform.scale())
+                / uniform_scale(portal_transform.scale()),
+        });
+    }
+}
@@ -0,0 +1,61 @@
+//! Displaying [`Portal`]s through UI.
+//!
+//! Rather than being rendered onto a mesh, a [`Portal`]'s image can be shown by a UI node, e.g. as
+//! a picture-in-picture view or a minimap in a corner of the screen. Spawn a [`PortalImageNode`]
+//! (a UI entity with an [`ImageNode`]) pointing at the portal, and leave the portal entity itself
+//! without a mesh. The portal still works out its camera from its [`Transform`] and
+//! [`Portal::target`] as usual.
+
+use bevy::prelude::*;
+
+use crate::{camera::PortalImage, Portal};
+
+/// Plugin that keeps the [`ImageNode`] of each [`PortalImageNode`] showing its portal's image.
+pub struct PortalUiPlugin;
+
+impl Plugin for PortalUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PortalImageNode>()
+            .add_systems(PostUpdate, update_image_nodes);
+    }
+}
+
+/// A UI node that displays the [`PortalImage`] of a [`Portal`].
+///
+/// The portal's image is sized to its [`Portal::primary_camera`]'s viewport, so the node should
+/// keep the same aspect ratio to avoid stretching the image (e.g. through [`Node::aspect_ratio`]).
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component, Debug)]
+#[require(ImageNode)]
+pub struct PortalImageNode {
+    /// The entity containing the [`Portal`] whose image is displayed.
+    pub portal: Entity,
+}
+
+impl PortalImageNode {
+    /// Creates a [`PortalImageNode`] that displays the image of `portal`.
+    #[inline]
+    #[must_use]
+    pub fn new(portal: Entity) -> Self {
+        Self { portal }
+    }
+}
+
+/// System that points the [`ImageNode`] of each [`PortalImageNode`] at its portal's
+/// [`PortalImage`].
+///
+/// The image is compared rather than reacted to, since it's replaced whenever the portal's camera
+/// is set up again.
+fn update_image_nodes(
+    mut image_node_query: Query<(&PortalImageNode, &mut ImageNode)>,
+    portal_query: Query<&PortalImage, With<Portal>>,
+) {
+    for (&PortalImageNode { portal }, mut image_node) in &mut image_node_query {
+        let Ok(portal_image) = portal_query.get(portal) else {
+            continue;
+        };
+        if image_node.image.id() != portal_image.id() {
+            image_node.image = portal_image.0.clone();
+        }
+    }
+}
@@ -0,0 +1,170 @@
+//! Viewing a [`Portal`] from several cameras, e.g. in split-screen.
+//!
+//! A [`Portal`] renders the view of a single [`Portal::primary_camera`]. To show the same portal to
+//! several cameras, insert [`PortalViewers`] next to it, listing each camera along with a render
+//! layer of its own. For every camera other than the primary camera, a [`Portal`] (with its own
+//! camera and image) is spawned as a child of the original one, sharing its mesh and settings. Each
+//! portal's mesh is placed on the layer of its camera, so that every camera only sees the view
+//! rendered for it.
+//!
+//! Each camera's [`RenderLayers`] must include its own layer (along with the layers of the rest of
+//! the scene), and exclude the layers of the other cameras.
+
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::Portal;
+
+/// Plugin that spawns a [`Portal`] for each camera of [`PortalViewers`].
+pub struct PortalViewersPlugin;
+
+impl Plugin for PortalViewersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostUpdate, sync_viewer_portals)
+            .add_observer(spawn_viewer_portals)
+            .add_observer(despawn_viewer_portals)
+            .register_type::<PortalViewers>();
+    }
+}
+
+/// Component that lets a [`Portal`] be viewed by several cameras, see the [module docs](self).
+///
+/// Insert this along with the [`Portal`]. [`Portal::primary_camera`] should be one of the viewers,
+/// otherwise it sees the portals of every other viewer.
+#[derive(Component, Reflect, Clone, Debug, Default)]
+#[reflect(Component, Debug, Default)]
+pub struct PortalViewers(pub Vec<PortalViewer>);
+
+/// A camera viewing a [`Portal`] through [`PortalViewers`].
+#[derive(Reflect, Clone, Copy, Debug)]
+pub struct PortalViewer {
+    /// The entity with the viewing [`Camera`].
+    pub camera: Entity,
+    /// The render layer that the portal's mesh is placed on for this camera, which should only be
+    /// visible to this camera.
+    pub layer: usize,
+}
+
+impl PortalViewer {
+    /// Creates a [`PortalViewer`] for `camera`, with its portal's mesh on `layer`.
+    #[inline]
+    #[must_use]
+    pub fn new(camera: Entity, layer: usize) -> Self {
+        Self { camera, layer }
+    }
+}
+
+/// Component that stores the [`Portal`]s spawned for the viewers of [`PortalViewers`].
+#[derive(Component, Debug)]
+pub struct PortalViewerPortals(pub Vec<Entity>);
+
+/// System that spawns a [`Portal`] for each viewer of [`PortalViewers`], other than
+/// [`Portal::primary_camera`], and places each portal's mesh on its viewer's layer.
+fn spawn_viewer_portals(
+    trigger: Trigger<OnAdd, PortalViewers>,
+    mut commands: Commands,
+    portal_query: Query<(&Portal, &PortalViewers, Option<&Mesh3d>)>,
+) {
+    let entity = trigger.entity();
+    let Ok((portal, viewers, mesh)) = portal_query.get(entity) else {
+        warn!("PortalViewers on {entity} has no effect, since the entity has no Portal");
+        return;
+    };
+
+    let mut viewer_portals = Vec::with_capacity(viewers.0.len());
+    let mut has_primary_camera = false;
+    for viewer in &viewers.0 {
+        let render_layers = RenderLayers::layer(viewer.layer);
+        if viewer.camera == portal.primary_camera {
+            commands.entity(entity).insert(render_layers);
+            has_primary_camera = true;
+            continue;
+        }
+
+        let mut viewer_portal = commands.spawn((
+            Name::new("Portal Viewer"),
+            Transform::default(),
+            copy_for_viewer(portal, viewer.camera),
+            render_layers,
+        ));
+        if let Some(mesh) = mesh {
+            viewer_portal.insert(mesh.clone());
+        }
+        viewer_portal.set_parent(entity);
+        viewer_portals.push(viewer_portal.id());
+    }
+    if !has_primary_camera {
+        warn!(
+            "primary camera of portal {entity} isn't one of its PortalViewers, so it sees the \
+            portals of every viewer"
+        );
+    }
+
+    commands
+        .entity(entity)
+        .insert(PortalViewerPortals(viewer_portals));
+}
+
+/// System that despawns the portals spawned for [`PortalViewers`] when it (or its [`Portal`]) is
+/// removed.
+fn despawn_viewer_portals(
+    trigger: Trigger<OnRemove, (Portal, PortalViewers)>,
+    mut commands: Commands,
+    viewer_portals_query: Query<&PortalViewerPortals>,
+) {
+    let entity = trigger.entity();
+    let Ok(viewer_portals) = viewer_portals_query.get(entity) else {
+        return;
+    };
+    // The portals are children, so they may already be despawned along with the entity
+    for &viewer_portal in &viewer_portals.0 {
+        if let Some(viewer_portal) = commands.get_entity(viewer_portal) {
+            viewer_portal.despawn_recursive();
+        }
+    }
+    if let Some(mut entity_commands) = commands.get_entity(entity) {
+        entity_commands.remove::<PortalViewerPortals>();
+    }
+}
+
+/// System that copies the settings of [`Portal`]s with [`PortalViewers`] to the portals spawned for
+/// their viewers whenever they change.
+///
+/// [`Portal::enabled`] isn't copied, since each viewer's portal is disabled on its own when its
+/// camera is removed; disable the portals of [`PortalViewerPortals`] to hide the portal from every
+/// viewer. Neither is the [`Portal::target`] of mirrors (see [`Portal::mirror_target`]), as each
+/// portal keeps the target it was set up with.
+fn sync_viewer_portals(
+    portal_query: Query<(&Portal, &PortalViewerPortals), Changed<Portal>>,
+    mut viewer_portal_query: Query<&mut Portal, Without<PortalViewerPortals>>,
+) {
+    for (portal, viewer_portals) in &portal_query {
+        for &viewer_portal in &viewer_portals.0 {
+            let Ok(mut viewer_portal) = viewer_portal_query.get_mut(viewer_portal) else {
+                continue;
+            };
+            let synced_portal = Portal {
+                linked_camera: viewer_portal.linked_camera,
+                enabled: viewer_portal.enabled,
+                target: if portal.mirror_target {
+                    viewer_portal.target
+                } else {
+                    portal.target
+                },
+                ..copy_for_viewer(portal, viewer_portal.primary_camera)
+            };
+            *viewer_portal = synced_portal;
+        }
+    }
+}
+
+/// Returns a copy of `portal` for `primary_camera`, with a camera of its own.
+///
+/// [`Portal::render_target`] isn't copied, since each viewer needs its own image.
+fn copy_for_viewer(portal: &Portal, primary_camera: Entity) -> Portal {
+    Portal {
+        primary_camera,
+        linked_camera: None,
+        render_target: None,
+        ..portal.clone()
+    }
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_easy_portals::Portal;
+
+#[test]
+fn portal_cameras_have_distinct_orders() {
+    let mut app = common::portal_app();
+
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let target = world.spawn(Transform::default()).id();
+    let portals: Vec<Entity> = (0..3)
+        .map(|_| world.spawn(Portal::new(primary_camera, target)).id())
+        .collect();
+    world.flush();
+
+    let orders: HashSet<isize> = portals
+        .iter()
+        .map(|&portal| {
+            let linked_camera = common::linked_camera(&app, portal);
+            app.world().get::<Camera>(linked_camera).unwrap().order
+        })
+        .collect();
+    assert_eq!(orders.len(), 3);
+
+    let primary_order = app.world().get::<Camera>(primary_camera).unwrap().order;
+    assert!(orders.iter().all(|&order| order < primary_order));
+}
@@ -0,0 +1,39 @@
+// Not every test uses every helper
+#![allow(dead_code)]
+
+use bevy::{prelude::*, render::camera::ManualTextureViews, window::PrimaryWindow};
+use bevy_easy_portals::{camera::PortalCameraPlugin, Portal};
+
+/// Creates an [`App`] with just enough set up for [`PortalCameraPlugin`]'s observers to run,
+/// without a renderer or windowing backend.
+pub fn portal_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        PortalCameraPlugin::default(),
+    ))
+    .init_asset::<Image>()
+    .init_resource::<ManualTextureViews>();
+    app.world_mut().spawn((Window::default(), PrimaryWindow));
+    app
+}
+
+/// Spawns a primary camera with `components`, a target and a [`Portal`] between them, returning
+/// the primary camera and portal entities.
+pub fn spawn_portal(app: &mut App, components: impl Bundle) -> (Entity, Entity) {
+    let world = app.world_mut();
+    let primary_camera = world.spawn((Camera3d::default(), components)).id();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    let portal = world.spawn(Portal::new(primary_camera, target)).id();
+    world.flush();
+    (primary_camera, portal)
+}
+
+/// Returns the [`Portal::linked_camera`] of `portal`.
+pub fn linked_camera(app: &App, portal: Entity) -> Entity {
+    app.world()
+        .get::<Portal>(portal)
+        .and_then(|portal| portal.linked_camera)
+        .expect("portal should have a linked camera")
+}
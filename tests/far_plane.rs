@@ -0,0 +1,36 @@
+mod common;
+
+use bevy::{prelude::*, render::camera::CameraProjection};
+use bevy_easy_portals::{projection::PortalProjection, Portal};
+
+fn far_projection(far: f32) -> Projection {
+    Projection::Perspective(PerspectiveProjection { far, ..default() })
+}
+
+#[test]
+fn portal_camera_far_plane_matches_primary_camera() {
+    let mut app = common::portal_app();
+    let (_, portal) = common::spawn_portal(&mut app, far_projection(500.0));
+
+    let linked_camera = common::linked_camera(&app, portal);
+    let portal_projection = app.world().get::<PortalProjection>(linked_camera).unwrap();
+    assert_eq!(portal_projection.far(), 500.0);
+}
+
+#[test]
+fn projection_override_uses_primary_camera_far_plane() {
+    let mut app = common::portal_app();
+    let world = app.world_mut();
+    let primary_camera = world
+        .spawn((Camera3d::default(), far_projection(500.0)))
+        .id();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    let portal = world
+        .spawn(Portal::new(primary_camera, target).with_projection(Some(far_projection(100.0))))
+        .id();
+    world.flush();
+
+    let linked_camera = common::linked_camera(&app, portal);
+    let portal_projection = app.world().get::<PortalProjection>(linked_camera).unwrap();
+    assert_eq!(portal_projection.far(), 500.0);
+}
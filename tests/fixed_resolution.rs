@@ -0,0 +1,64 @@
+mod common;
+
+use bevy::{
+    pbr::DirectionalLightShadowMap, prelude::*, render::camera::CameraProjectionPlugin,
+    window::ExitCondition,
+};
+use bevy_easy_portals::{projection::PortalProjection, Portal};
+
+#[test]
+fn fixed_resolution_keeps_primary_aspect_ratio() {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+        CameraProjectionPlugin::<Projection>::default(),
+    ))
+    .init_resource::<DirectionalLightShadowMap>();
+
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    // A square monitor in a 16:9 window
+    let portal = world
+        .spawn(Portal::new(primary_camera, target).with_resolution(UVec2::splat(512)))
+        .id();
+    app.update();
+    app.update();
+
+    let window_size = app
+        .world_mut()
+        .query::<&Window>()
+        .single(app.world())
+        .size();
+    let linked_camera = common::linked_camera(&app, portal);
+    let world = app.world();
+    let Projection::Perspective(perspective) = &world
+        .get::<PortalProjection>(linked_camera)
+        .unwrap()
+        .projection
+    else {
+        panic!("portal projection should be perspective");
+    };
+    let window_aspect_ratio = window_size.x / window_size.y;
+    assert!(
+        (perspective.aspect_ratio - window_aspect_ratio).abs() < 1e-4,
+        "portal aspect ratio {} should match the window's {window_aspect_ratio}",
+        perspective.aspect_ratio
+    );
+
+    // The camera's matrix should match the primary camera's, rather than the image's
+    let clip_from_view = world.get::<Camera>(linked_camera).unwrap().clip_from_view();
+    let primary_clip_from_view = world
+        .get::<Camera>(primary_camera)
+        .unwrap()
+        .clip_from_view();
+    assert!(
+        (clip_from_view.x_axis.x - primary_clip_from_view.x_axis.x).abs() < 1e-4,
+        "portal view should be stretched like the primary camera's"
+    );
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use bevy::{
+    core_pipeline::{
+        prepass::{DepthPrepass, NormalPrepass},
+        Skybox,
+    },
+    prelude::*,
+};
+use bevy_easy_portals::{camera::PortalInheritance, Portal};
+
+fn skybox(app: &mut App) -> Skybox {
+    let image = app
+        .world_mut()
+        .resource_mut::<Assets<Image>>()
+        .add(Image::default());
+    Skybox {
+        image,
+        brightness: 1000.0,
+        rotation: Quat::IDENTITY,
+    }
+}
+
+#[test]
+fn portal_camera_inherits_skybox() {
+    let mut app = common::portal_app();
+    let skybox = skybox(&mut app);
+    let (_, portal) = common::spawn_portal(&mut app, skybox.clone());
+
+    let linked_camera = common::linked_camera(&app, portal);
+    let portal_skybox = app.world().get::<Skybox>(linked_camera).unwrap();
+    assert_eq!(portal_skybox.image.id(), skybox.image.id());
+    assert_eq!(portal_skybox.brightness, skybox.brightness);
+}
+
+#[test]
+fn portal_camera_skybox_inheritance_can_be_disabled() {
+    let mut app = common::portal_app();
+    let skybox = skybox(&mut app);
+
+    let world = app.world_mut();
+    let primary_camera = world.spawn((Camera3d::default(), skybox)).id();
+    let target = world.spawn(Transform::default()).id();
+    let portal = world
+        .spawn(
+            Portal::new(primary_camera, target).with_inheritance(PortalInheritance {
+                skybox: false,
+                ..default()
+            }),
+        )
+        .id();
+    world.flush();
+
+    let linked_camera = common::linked_camera(&app, portal);
+    assert!(app.world().get::<Skybox>(linked_camera).is_none());
+}
+
+#[test]
+fn portal_camera_inherits_depth_prepass() {
+    let mut app = common::portal_app();
+    let (_, portal) = common::spawn_portal(&mut app, DepthPrepass);
+
+    let linked_camera = common::linked_camera(&app, portal);
+    let world = app.world();
+    assert!(world.get::<DepthPrepass>(linked_camera).is_some());
+    assert!(world.get::<NormalPrepass>(linked_camera).is_none());
+}
+
+#[test]
+fn portal_camera_prepass_inheritance_can_be_disabled() {
+    let mut app = common::portal_app();
+
+    let world = app.world_mut();
+    let primary_camera = world
+        .spawn((Camera3d::default(), DepthPrepass, NormalPrepass))
+        .id();
+    let target = world.spawn(Transform::default()).id();
+    let portal = world
+        .spawn(
+            Portal::new(primary_camera, target).with_inheritance(PortalInheritance {
+                depth_prepass: false,
+                ..default()
+            }),
+        )
+        .id();
+    world.flush();
+
+    let linked_camera = common::linked_camera(&app, portal);
+    let world = app.world();
+    assert!(world.get::<DepthPrepass>(linked_camera).is_none());
+    assert!(world.get::<NormalPrepass>(linked_camera).is_some());
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use bevy::{pbr::DirectionalLightShadowMap, prelude::*, window::ExitCondition};
+use bevy_easy_portals::Portal;
+
+/// Marks the mirror moved by [`move_mirror`].
+#[derive(Component)]
+struct MovingMirror;
+
+/// Creates an [`App`] like [`common::portal_app`] that can be updated, with a mirror moved by
+/// [`move_mirror`] every frame, returning the mirror and its target entities.
+fn moving_mirror_app() -> (App, Entity, Entity) {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_resource::<DirectionalLightShadowMap>()
+    .add_systems(Update, move_mirror);
+
+    let world = app.world_mut();
+    let primary_camera = world
+        .spawn((Camera3d::default(), Transform::from_xyz(1.0, 2.0, 5.0)))
+        .id();
+    let mirror = world
+        .spawn((Portal::mirror(primary_camera), MovingMirror))
+        .id();
+    app.update();
+
+    let target = app.world().get::<Portal>(mirror).unwrap().target;
+    assert_ne!(target, Entity::PLACEHOLDER, "mirror should spawn a target");
+    (app, mirror, target)
+}
+
+/// Moves and turns the [`MovingMirror`], like a swinging door.
+fn move_mirror(mut mirror_transform: Single<&mut Transform, With<MovingMirror>>) {
+    mirror_transform.translation += Vec3::new(0.5, 0.0, -0.25);
+    mirror_transform.rotate_y(0.3);
+}
+
+/// Asserts that the target of `mirror` faces away from it from the same position, and that the
+/// mirror's camera sees the primary camera's view reflected.
+fn assert_mirrored(app: &App, mirror: Entity, target: Entity) {
+    let world = app.world();
+    let portal = world.get::<Portal>(mirror).unwrap();
+    let mirror_transform = world.get::<GlobalTransform>(mirror).unwrap();
+    let target_transform = world.get::<GlobalTransform>(target).unwrap();
+    assert!(
+        target_transform
+            .translation()
+            .distance(mirror_transform.translation())
+            < 1e-4,
+        "target should be at the mirror"
+    );
+    assert!(
+        target_transform.forward().dot(*mirror_transform.forward()) < -0.9999,
+        "target should face away from the mirror"
+    );
+
+    // The primary camera relative to the mirror is the portal camera relative to the target
+    let primary_translation = world
+        .get::<GlobalTransform>(portal.primary_camera)
+        .unwrap()
+        .translation();
+    let camera_translation = world
+        .get::<GlobalTransform>(common::linked_camera(app, mirror))
+        .unwrap()
+        .translation();
+    let expected = mirror_transform
+        .affine()
+        .inverse()
+        .transform_point3(primary_translation);
+    let actual = target_transform
+        .affine()
+        .inverse()
+        .transform_point3(camera_translation);
+    assert!(
+        actual.distance(expected) < 1e-3,
+        "portal camera at {actual} relative to the target should be at {expected}"
+    );
+}
+
+#[test]
+fn moving_mirror_keeps_target_mirrored() {
+    let (mut app, mirror, target) = moving_mirror_app();
+
+    for _ in 0..3 {
+        app.update();
+        assert_mirrored(&app, mirror, target);
+    }
+}
+
+#[test]
+fn mirror_target_is_kept_mirrored_without_parent() {
+    let (mut app, mirror, target) = moving_mirror_app();
+    app.world_mut().entity_mut(target).remove_parent();
+    // Moving the target by hand is undone
+    app.world_mut()
+        .get_mut::<Transform>(target)
+        .unwrap()
+        .translation = Vec3::splat(100.0);
+
+    for _ in 0..3 {
+        app.update();
+        assert_mirrored(&app, mirror, target);
+    }
+    assert!(app.world().get::<Parent>(target).is_none());
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use bevy::{
+    ecs::schedule::ScheduleLabel, pbr::DirectionalLightShadowMap, prelude::*, window::ExitCondition,
+};
+use bevy_easy_portals::Portal;
+
+/// Marks the [`Portal::target`] moved by [`move_target`].
+#[derive(Component)]
+struct MovingTarget;
+
+/// Creates an [`App`] like [`common::portal_app`] that can be updated, with a [`Portal`] whose
+/// target is moved by [`move_target`] in `schedule`, returning the portal and target entities.
+fn moving_target_app(schedule: impl ScheduleLabel) -> (App, Entity, Entity) {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_resource::<DirectionalLightShadowMap>()
+    .add_systems(
+        schedule,
+        move_target.before(TransformSystem::TransformPropagate),
+    );
+
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let target = world.spawn((Transform::default(), MovingTarget)).id();
+    let portal = world.spawn(Portal::new(primary_camera, target)).id();
+    app.update();
+    (app, portal, target)
+}
+
+/// Moves the [`MovingTarget`] along the x axis, like a moving platform.
+fn move_target(mut target_transform: Single<&mut Transform, With<MovingTarget>>) {
+    target_transform.translation.x += 1.0;
+}
+
+/// Asserts that the [`Portal::linked_camera`] of `portal` is where its `target` is this frame.
+///
+/// With the portal and primary camera both at the origin, the portal camera coincides with the
+/// target.
+fn assert_camera_follows_target(app: &App, portal: Entity, target: Entity) {
+    let linked_camera = common::linked_camera(app, portal);
+    let world = app.world();
+    let target_translation = world.get::<GlobalTransform>(target).unwrap().translation();
+    let camera_translation = world
+        .get::<GlobalTransform>(linked_camera)
+        .unwrap()
+        .translation();
+    assert!(
+        camera_translation.distance(target_translation) < 1e-4,
+        "portal camera at {camera_translation} should be at the target's {target_translation}"
+    );
+}
+
+#[test]
+fn portal_camera_follows_target_moved_in_update() {
+    let (mut app, portal, target) = moving_target_app(Update);
+
+    for _ in 0..3 {
+        app.update();
+        assert_camera_follows_target(&app, portal, target);
+    }
+    let target_transform = app.world().get::<GlobalTransform>(target).unwrap();
+    assert!(target_transform.translation().x >= 3.0);
+}
+
+#[test]
+fn portal_camera_follows_target_animated_in_post_update() {
+    let (mut app, portal, target) = moving_target_app(PostUpdate);
+
+    for _ in 0..3 {
+        app.update();
+        assert_camera_follows_target(&app, portal, target);
+    }
+}
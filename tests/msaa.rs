@@ -0,0 +1,21 @@
+mod common;
+
+use bevy::prelude::*;
+
+#[test]
+fn portal_camera_inherits_msaa() {
+    let mut app = common::portal_app();
+    let (_, portal) = common::spawn_portal(&mut app, Msaa::Sample2);
+
+    let linked_camera = common::linked_camera(&app, portal);
+    assert_eq!(app.world().get::<Msaa>(linked_camera), Some(&Msaa::Sample2));
+}
+
+#[test]
+fn portal_camera_inherits_disabled_msaa() {
+    let mut app = common::portal_app();
+    let (_, portal) = common::spawn_portal(&mut app, Msaa::Off);
+
+    let linked_camera = common::linked_camera(&app, portal);
+    assert_eq!(app.world().get::<Msaa>(linked_camera), Some(&Msaa::Off));
+}
@@ -0,0 +1,336 @@
+#![cfg(feature = "picking")]
+
+mod common;
+
+use bevy::{
+    input::InputPlugin,
+    pbr::DirectionalLightShadowMap,
+    picking::{
+        backend::{HitData, PointerHits},
+        pointer::{
+            Location, PointerAction, PointerButton, PointerId, PointerInput, PressDirection,
+        },
+        PickSet,
+    },
+    prelude::*,
+    render::{
+        camera::{CameraProjectionPlugin, NormalizedRenderTarget},
+        view::VisibilityPlugin,
+    },
+    utils::HashMap,
+    window::{ExitCondition, PrimaryWindow, WindowRef},
+};
+use bevy_easy_portals::{
+    camera::PortalImage,
+    picking::{PortalPickingPlugin, PortalPointer},
+    Portal,
+};
+
+/// The [`PointerInput`]s sent for portal pointers, in the order they were sent.
+#[derive(Resource, Default)]
+struct PortalPointerInputs(Vec<PointerInput>);
+
+/// Creates an [`App`] that can be updated, with picking "through" portals enabled.
+///
+/// There is no picking backend, instead the portal is reported as hit by every pointer (see
+/// [`hit_portals`]).
+fn picking_app() -> App {
+    let mut app = picking_app_without_portal_picking();
+    app.add_plugins(PortalPickingPlugin);
+    app
+}
+
+/// Creates an [`App`] like [`picking_app`], but without [`PortalPickingPlugin`].
+fn picking_app_without_portal_picking() -> App {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+        InputPlugin,
+        VisibilityPlugin,
+        CameraProjectionPlugin::<Projection>::default(),
+        DefaultPickingPlugins,
+    ))
+    .init_asset::<Mesh>()
+    .init_resource::<DirectionalLightShadowMap>()
+    .init_resource::<PortalPointerInputs>()
+    .add_systems(PreUpdate, hit_portals.in_set(PickSet::Backend))
+    .add_systems(Last, record_portal_inputs);
+    app
+}
+
+/// Picking backend that reports every [`Portal`] as hit by every pointer, except for the pointers
+/// forwarded through portals.
+fn hit_portals(
+    portal_query: Query<(Entity, &Portal)>,
+    pointer_query: Query<&PointerId, Without<PortalPointer>>,
+    mut pointer_hits: EventWriter<PointerHits>,
+) {
+    for (entity, portal) in &portal_query {
+        for &pointer_id in &pointer_query {
+            let hit = HitData::new(portal.primary_camera, 1.0, None, None);
+            pointer_hits.send(PointerHits::new(pointer_id, vec![(entity, hit)], 0.0));
+        }
+    }
+}
+
+fn record_portal_inputs(
+    mut pointer_inputs: EventReader<PointerInput>,
+    mut recorded: ResMut<PortalPointerInputs>,
+) {
+    recorded.0.extend(
+        pointer_inputs
+            .read()
+            .filter(|input| matches!(input.pointer_id, PointerId::Custom(_)))
+            .cloned(),
+    );
+}
+
+/// Spawns a primary camera, a target and a [`Portal`] with a 2x2 [`Rectangle`] mesh in the middle
+/// of the primary camera's view, returning the portal entity.
+fn spawn_portal(app: &mut App) -> Entity {
+    let mesh = app
+        .world_mut()
+        .resource_mut::<Assets<Mesh>>()
+        .add(Rectangle::new(2.0, 2.0));
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    let portal = world
+        .spawn((
+            Mesh3d(mesh),
+            Transform::from_xyz(0.0, 0.0, -5.0),
+            Portal::new(primary_camera, target),
+        ))
+        .id();
+    app.update();
+    app.update();
+    portal
+}
+
+/// Sends a [`PointerInput`] at a position in the primary window for each of `inputs`, then updates
+/// `app` twice so that any input forwarded through a portal is sent.
+fn send_inputs(app: &mut App, inputs: impl IntoIterator<Item = (PointerId, Vec2, PointerAction)>) {
+    let window = app
+        .world_mut()
+        .query_filtered::<Entity, With<PrimaryWindow>>()
+        .single(app.world());
+    let target =
+        NormalizedRenderTarget::Window(WindowRef::Primary.normalize(Some(window)).unwrap());
+    for (pointer_id, position, action) in inputs {
+        app.world_mut().send_event(PointerInput::new(
+            pointer_id,
+            Location {
+                target: target.clone(),
+                position,
+            },
+            action,
+        ));
+    }
+    app.update();
+    app.update();
+}
+
+/// Sends a mouse [`PointerInput`] (see [`send_inputs`]).
+fn send_mouse_input(app: &mut App, position: Vec2, action: PointerAction) {
+    send_inputs(app, [(PointerId::Mouse, position, action)]);
+}
+
+#[test]
+fn drag_leaving_portal_stops_forwarding_moves() {
+    let mut app = picking_app();
+    spawn_portal(&mut app);
+
+    let center = Vec2::new(640.0, 360.0);
+    let outside = Vec2::new(1200.0, 360.0);
+    let press = PointerAction::Pressed {
+        direction: PressDirection::Down,
+        button: PointerButton::Primary,
+    };
+    let release = PointerAction::Pressed {
+        direction: PressDirection::Up,
+        button: PointerButton::Primary,
+    };
+    send_mouse_input(&mut app, center, PointerAction::Moved { delta: Vec2::ZERO });
+    send_mouse_input(&mut app, center, press);
+    send_mouse_input(
+        &mut app,
+        center + 10.0,
+        PointerAction::Moved { delta: Vec2::ONE },
+    );
+    let forwarded = app.world().resource::<PortalPointerInputs>().0.len();
+    assert_eq!(forwarded, 3, "inputs over the portal should be forwarded");
+
+    send_mouse_input(
+        &mut app,
+        outside,
+        PointerAction::Moved {
+            delta: outside - center,
+        },
+    );
+    let inputs = &app.world().resource::<PortalPointerInputs>().0;
+    assert_eq!(
+        inputs.len(),
+        forwarded,
+        "moves outside of the portal shouldn't be forwarded"
+    );
+
+    send_mouse_input(&mut app, outside, release);
+    let inputs = &app.world().resource::<PortalPointerInputs>().0;
+    assert_eq!(
+        inputs.len(),
+        forwarded + 1,
+        "the release should be forwarded"
+    );
+    let PointerInput {
+        location, action, ..
+    } = inputs.last().unwrap();
+    assert!(matches!(
+        action,
+        PointerAction::Pressed {
+            direction: PressDirection::Up,
+            ..
+        }
+    ));
+    let image_size = Vec2::new(1280.0, 720.0);
+    assert!(
+        location.position.cmpge(Vec2::ZERO).all() && location.position.cmple(image_size).all(),
+        "the release should be within the portal's image, was at {}",
+        location.position
+    );
+}
+
+#[test]
+fn pointers_are_forwarded_independently() {
+    let mut app = picking_app();
+    let portal = spawn_portal(&mut app);
+    let touch = PointerId::Touch(0);
+    app.world_mut().spawn(touch);
+
+    let mouse_position = Vec2::new(640.0, 360.0);
+    let touch_position = Vec2::new(660.0, 380.0);
+    let moved = PointerAction::Moved { delta: Vec2::ZERO };
+    send_inputs(
+        &mut app,
+        [
+            (PointerId::Mouse, mouse_position, moved),
+            (touch, touch_position, moved),
+        ],
+    );
+
+    let world = app.world_mut();
+    let portal_pointers: HashMap<PointerId, PointerId> = world
+        .query::<(&Parent, &PortalPointer, &PointerId)>()
+        .iter(world)
+        .filter(|(parent, ..)| parent.get() == portal)
+        .map(|(_, portal_pointer, &pointer_id)| (portal_pointer.source, pointer_id))
+        .collect();
+    assert_eq!(
+        portal_pointers.len(),
+        2,
+        "each pointer should have a portal pointer"
+    );
+    assert_ne!(portal_pointers[&PointerId::Mouse], portal_pointers[&touch]);
+
+    let inputs = &world.resource::<PortalPointerInputs>().0;
+    assert_eq!(
+        inputs.len(),
+        2,
+        "the inputs of both pointers should be forwarded"
+    );
+    for (source, position) in [(PointerId::Mouse, mouse_position), (touch, touch_position)] {
+        let input = inputs
+            .iter()
+            .find(|input| input.pointer_id == portal_pointers[&source])
+            .expect("input should be forwarded through the pointer's portal pointer");
+        assert!(input.location.position.distance(position) < 0.1);
+    }
+
+    // Ending the touch shouldn't affect the mouse
+    let touch_entity = world
+        .query::<(Entity, &PointerId)>()
+        .iter(world)
+        .find_map(|(entity, &pointer_id)| (pointer_id == touch).then_some(entity))
+        .unwrap();
+    world.despawn(touch_entity);
+    send_mouse_input(&mut app, mouse_position + 10.0, moved);
+
+    let world = app.world_mut();
+    let sources: Vec<PointerId> = world
+        .query::<&PortalPointer>()
+        .iter(world)
+        .map(|portal_pointer| portal_pointer.source)
+        .collect();
+    assert_eq!(sources, [PointerId::Mouse]);
+    let children = world.get::<Children>(portal).unwrap();
+    assert!(
+        children
+            .iter()
+            .all(|&child| world.get_entity(child).is_ok()),
+        "the touch's portal pointer should be removed from the portal's children"
+    );
+    let last_input = world.resource::<PortalPointerInputs>().0.last().unwrap();
+    assert_eq!(last_input.pointer_id, portal_pointers[&PointerId::Mouse]);
+}
+
+#[test]
+fn portals_spawned_before_picking_plugin_are_picked_through() {
+    let mut app = picking_app_without_portal_picking();
+    let portal = spawn_portal(&mut app);
+    assert!(
+        app.world().get::<PortalImage>(portal).is_some(),
+        "portal should be set up before the picking plugin is added"
+    );
+    app.add_plugins(PortalPickingPlugin);
+
+    send_mouse_input(
+        &mut app,
+        Vec2::new(640.0, 360.0),
+        PointerAction::Moved { delta: Vec2::ZERO },
+    );
+
+    let world = app.world_mut();
+    let portal_pointers: Vec<PointerId> = world
+        .query::<(&Parent, &PointerId, &PortalPointer)>()
+        .iter(world)
+        .filter(|(parent, ..)| parent.get() == portal)
+        .map(|(_, &pointer_id, _)| pointer_id)
+        .collect();
+    assert_eq!(
+        portal_pointers.len(),
+        1,
+        "the portal should have a pointer once hovered"
+    );
+    let inputs = &world.resource::<PortalPointerInputs>().0;
+    assert_eq!(inputs.len(), 1, "the move should be forwarded");
+    assert_eq!(inputs[0].pointer_id, portal_pointers[0]);
+}
+
+#[test]
+fn pointers_are_forwarded_into_resized_portal_images() {
+    let mut app = picking_app();
+    let portal = spawn_portal(&mut app);
+    app.world_mut()
+        .get_mut::<Portal>(portal)
+        .unwrap()
+        .resolution_scale = 0.5;
+    // The portal camera's viewport is updated from the image's asset events, a frame after the
+    // image is resized
+    app.update();
+    app.update();
+
+    let center = Vec2::new(640.0, 360.0);
+    send_mouse_input(&mut app, center, PointerAction::Moved { delta: Vec2::ZERO });
+
+    let inputs = &app.world().resource::<PortalPointerInputs>().0;
+    assert_eq!(inputs.len(), 1, "the move should be forwarded");
+    assert!(
+        inputs[0].location.position.distance(center / 2.0) < 0.1,
+        "the move should be scaled to the resized image, was at {}",
+        inputs[0].location.position
+    );
+}
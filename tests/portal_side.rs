@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+use bevy_easy_portals::Portal;
+
+/// A portal facing `+Z` (like a [`Rectangle`]), rotated and moved away from the origin to make sure
+/// its transform is taken into account.
+fn portal_transform() -> GlobalTransform {
+    GlobalTransform::from(
+        Transform::from_xyz(3.0, 1.0, -5.0).with_rotation(Quat::from_rotation_y(0.5)),
+    )
+}
+
+/// Returns the transform of a camera at `local_position` relative to [`portal_transform`], looking
+/// at the portal.
+fn camera_transform(local_position: Vec3) -> GlobalTransform {
+    let portal_transform = portal_transform();
+    let translation = portal_transform.transform_point(local_position);
+    GlobalTransform::from(
+        Transform::from_translation(translation)
+            .looking_at(portal_transform.translation(), Vec3::Y),
+    )
+}
+
+#[test]
+fn camera_in_front_of_portal_is_not_on_back_side() {
+    let camera_transform = camera_transform(Vec3::new(0.5, 0.0, 4.0));
+    assert!(!Portal::is_camera_on_back_side(
+        &portal_transform(),
+        &camera_transform
+    ));
+}
+
+#[test]
+fn camera_behind_portal_is_on_back_side() {
+    let camera_transform = camera_transform(Vec3::new(0.5, 0.0, -4.0));
+    assert!(Portal::is_camera_on_back_side(
+        &portal_transform(),
+        &camera_transform
+    ));
+}
+
+#[test]
+fn camera_edge_on_to_portal_is_on_back_side() {
+    let portal_transform = portal_transform();
+    // Exactly on the portal's plane, avoiding any rounding from transforming a local position
+    let translation = portal_transform.translation() + portal_transform.right() * 4.0;
+    let camera_transform = GlobalTransform::from(Transform::from_translation(translation));
+    assert!(
+        Portal::is_camera_on_back_side(&portal_transform, &camera_transform),
+        "a camera seeing the portal edge-on should count as being behind it"
+    );
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    render::camera::{ManualTextureViews, Viewport},
+    window::{ExitCondition, PrimaryWindow},
+};
+use bevy_easy_portals::{
+    camera::{PortalCameraPlugin, PortalImage},
+    projection::PortalProjection,
+    Portal,
+};
+
+/// Creates an [`App`] like [`common::portal_app`], that can also be updated.
+fn relink_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        PortalCameraPlugin::default(),
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_resource::<ManualTextureViews>()
+    .init_resource::<DirectionalLightShadowMap>();
+    app.world_mut().spawn((Window::default(), PrimaryWindow));
+    app
+}
+
+/// Returns a [`Camera`] with the given `order`, rendering to a viewport of the given `size`.
+fn camera_with_viewport(order: isize, size: UVec2) -> Camera {
+    Camera {
+        order,
+        viewport: Some(Viewport {
+            physical_size: size,
+            ..default()
+        }),
+        ..default()
+    }
+}
+
+#[test]
+fn portal_camera_follows_swapped_primary_camera() {
+    let mut app = relink_app();
+    let (_, portal) = common::spawn_portal(
+        &mut app,
+        (
+            camera_with_viewport(0, UVec2::new(640, 360)),
+            Tonemapping::TonyMcMapface,
+        ),
+    );
+    app.update();
+
+    let image = app
+        .world()
+        .get::<PortalImage>(portal)
+        .expect("portal should have an image")
+        .0
+        .clone_weak();
+    let linked_camera = common::linked_camera(&app, portal);
+
+    // E.g. a cutscene camera taking over, rendering above the previous camera
+    let cutscene_camera = app
+        .world_mut()
+        .spawn((
+            Camera3d::default(),
+            camera_with_viewport(10, UVec2::new(320, 180)),
+            Projection::Perspective(PerspectiveProjection {
+                fov: FRAC_PI_2,
+                ..default()
+            }),
+            Tonemapping::AgX,
+        ))
+        .id();
+    app.world_mut()
+        .get_mut::<Portal>(portal)
+        .unwrap()
+        .primary_camera = cutscene_camera;
+    app.update();
+
+    assert_eq!(
+        common::linked_camera(&app, portal),
+        linked_camera,
+        "the portal camera should be kept rather than respawned"
+    );
+    let portal_image = app.world().get::<PortalImage>(portal).unwrap();
+    assert_eq!(portal_image.id(), image.id(), "the image should be kept");
+    assert_eq!(
+        app.world()
+            .resource::<Assets<Image>>()
+            .get(&image)
+            .expect("portal image should exist")
+            .size(),
+        UVec2::new(320, 180),
+        "the image should be resized to the new primary camera's viewport"
+    );
+
+    let camera = app.world().get::<Camera>(linked_camera).unwrap();
+    assert!(
+        camera.order < 10,
+        "the portal camera should render before the new primary camera, but has order {}",
+        camera.order
+    );
+    assert_eq!(
+        app.world().get::<Tonemapping>(linked_camera),
+        Some(&Tonemapping::AgX)
+    );
+    let Some(PortalProjection {
+        projection: Projection::Perspective(perspective),
+        ..
+    }) = app.world().get::<PortalProjection>(linked_camera)
+    else {
+        panic!("the portal camera should have a perspective PortalProjection");
+    };
+    assert_eq!(
+        perspective.fov, FRAC_PI_2,
+        "the portal camera should inherit the new primary camera's field of view"
+    );
+}
@@ -0,0 +1,230 @@
+//! Tests that render portals on the GPU and check the resulting images.
+//!
+//! These need a GPU (or a software renderer) to run, so they're ignored by default. Run them with
+//! `cargo test --test render -- --ignored`, e.g. with `WGPU_BACKEND=gl` to use Mesa's llvmpipe.
+
+use bevy::{
+    core_pipeline::tonemapping::Tonemapping,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        RenderPlugin,
+    },
+    window::ExitCondition,
+};
+use bevy_easy_portals::{
+    snapshot::{PortalSnapshot, RequestPortalSnapshot},
+    Portal, PortalPlugins,
+};
+
+/// The number of frames to render before requesting a snapshot, and to wait for the snapshot.
+const FRAMES: usize = 10;
+
+/// The [`PortalSnapshot`] received by the test, if any.
+#[derive(Resource, Default)]
+struct ReceivedSnapshot(Option<Image>);
+
+/// Creates an [`App`] that renders without a window.
+fn render_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        HierarchyPlugin,
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        AssetPlugin::default(),
+        RenderPlugin::default(),
+        ImagePlugin::default(),
+        bevy::core_pipeline::CorePipelinePlugin,
+        bevy::pbr::PbrPlugin::default(),
+    ));
+    // 2D portal materials need the sprite pipelines
+    #[cfg(feature = "2d")]
+    app.add_plugins(bevy::sprite::SpritePlugin { add_picking: false });
+    app.add_plugins(PortalPlugins)
+        .init_resource::<ReceivedSnapshot>();
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Returns an unlit [`StandardMaterial`] of the given `color`, so that no lights are needed.
+fn unlit(color: Color) -> StandardMaterial {
+    StandardMaterial {
+        base_color: color,
+        unlit: true,
+        ..default()
+    }
+}
+
+/// Spawns a primary camera rendering to an image, looking at a green wall through a portal.
+///
+/// The portal's target is far away, facing a red wall. A blue cube sits between the portal camera
+/// and the target, which should be clipped by the oblique near plane. Returns the portal entity.
+fn spawn_scene(app: &mut App) -> Entity {
+    let world = app.world_mut();
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: 256,
+            height: 256,
+            ..default()
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        default(),
+    );
+    image.texture_descriptor.usage |= TextureUsages::RENDER_ATTACHMENT;
+    let image = world.resource_mut::<Assets<Image>>().add(image);
+
+    let mut meshes = world.resource_mut::<Assets<Mesh>>();
+    let wall = meshes.add(Rectangle::new(50.0, 50.0));
+    let cube = meshes.add(Cuboid::from_length(2.0));
+    let portal_mesh = meshes.add(Rectangle::new(2.0, 2.0));
+    let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+    let green = materials.add(unlit(Color::srgb(0.0, 1.0, 0.0)));
+    let red = materials.add(unlit(Color::srgb(1.0, 0.0, 0.0)));
+    let blue = materials.add(unlit(Color::srgb(0.0, 0.0, 1.0)));
+
+    let primary_camera = world
+        .spawn((
+            Camera3d::default(),
+            Camera {
+                target: RenderTarget::Image(image),
+                clear_color: ClearColorConfig::Custom(Color::BLACK),
+                ..default()
+            },
+            // Keep the colors as they are, so they can be compared exactly
+            Tonemapping::None,
+        ))
+        .id();
+
+    // Seen around the portal by the primary camera
+    world.spawn((
+        Mesh3d(wall.clone()),
+        MeshMaterial3d(green),
+        Transform::from_xyz(0.0, 0.0, -20.0),
+    ));
+
+    // Seen through the portal, with the portal camera ending up at (100, 0, 5)
+    let target = world.spawn(Transform::from_xyz(100.0, 0.0, 0.0)).id();
+    world.spawn((
+        Mesh3d(wall),
+        MeshMaterial3d(red),
+        Transform::from_xyz(100.0, 0.0, -20.0),
+    ));
+    world.spawn((
+        Mesh3d(cube),
+        MeshMaterial3d(blue),
+        Transform::from_xyz(100.0, 0.0, 2.5),
+    ));
+
+    world
+        .spawn((
+            Mesh3d(portal_mesh),
+            Transform::from_xyz(0.0, 0.0, -5.0),
+            Portal::new(primary_camera, target),
+        ))
+        .id()
+}
+
+/// Renders a few frames, then reads back the [`bevy_easy_portals::camera::PortalImage`] of
+/// `portal`.
+fn render_snapshot(app: &mut App, portal: Entity) -> Image {
+    for _ in 0..FRAMES {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    world.entity_mut(portal).observe(
+        |trigger: Trigger<PortalSnapshot>, mut received: ResMut<ReceivedSnapshot>| {
+            received.0 = Some(trigger.event().0.clone());
+        },
+    );
+    world.trigger_targets(RequestPortalSnapshot, portal);
+
+    for _ in 0..FRAMES {
+        app.update();
+        if let Some(image) = app.world_mut().resource_mut::<ReceivedSnapshot>().0.take() {
+            return image;
+        }
+    }
+    panic!("portal snapshot wasn't received within {FRAMES} frames");
+}
+
+/// Returns the RGB bytes of the pixel in the center of a [`TextureFormat::Bgra8UnormSrgb`] `image`.
+fn center_pixel(image: &Image) -> [u8; 3] {
+    assert_eq!(
+        image.texture_descriptor.format,
+        TextureFormat::Bgra8UnormSrgb
+    );
+    let size = image.size();
+    let index = ((size.y / 2 * size.x + size.x / 2) * 4) as usize;
+    let [b, g, r] = image.data[index..index + 3] else {
+        unreachable!();
+    };
+    [r, g, b]
+}
+
+#[test]
+#[ignore = "requires a GPU"]
+fn portal_renders_target_view() {
+    let mut app = render_app();
+    let portal = spawn_scene(&mut app);
+
+    let image = render_snapshot(&mut app, portal);
+    let [r, g, b] = center_pixel(&image);
+    assert!(
+        r > 200 && g < 50 && b < 50,
+        "the center of the portal should show the red wall behind the target, without the blue \
+        cube in front of it, but was ({r}, {g}, {b})"
+    );
+}
+
+#[test]
+#[ignore = "requires a GPU"]
+fn portal_without_shadows_keeps_directional_light() {
+    for render_shadows in [true, false] {
+        let mut app = render_app();
+        let portal = spawn_scene(&mut app);
+        let world = app.world_mut();
+        // Light every material, with the blue cube casting a shadow on the center of the red wall
+        for (_, material) in world.resource_mut::<Assets<StandardMaterial>>().iter_mut() {
+            material.unlit = false;
+        }
+        world.insert_resource(AmbientLight {
+            brightness: 0.0,
+            ..default()
+        });
+        world.spawn((
+            DirectionalLight {
+                illuminance: 10_000.0,
+                shadows_enabled: true,
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, 10.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ));
+        world.get_mut::<Portal>(portal).unwrap().render_shadows = render_shadows;
+
+        let image = render_snapshot(&mut app, portal);
+        let [r, g, b] = center_pixel(&image);
+        if render_shadows {
+            assert!(
+                r < 50,
+                "the center of the portal should be in the cube's shadow, but was ({r}, {g}, {b})"
+            );
+        } else {
+            assert!(
+                r > 200,
+                "the center of the portal should be lit without the cube's shadow, but was \
+                ({r}, {g}, {b})"
+            );
+        }
+    }
+}
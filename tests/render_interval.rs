@@ -0,0 +1,86 @@
+mod common;
+
+use bevy::{pbr::DirectionalLightShadowMap, prelude::*, utils::HashMap, window::ExitCondition};
+use bevy_easy_portals::{
+    camera::{PortalFrameCount, PortalReady, PortalRenderedFrame},
+    Portal,
+};
+
+/// The [`PortalFrameCount`] each portal triggered [`PortalReady`] on.
+#[derive(Resource, Default)]
+struct ReadyFrames(HashMap<Entity, u32>);
+
+#[test]
+fn portals_render_on_their_interval_and_phase() {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_resource::<DirectionalLightShadowMap>()
+    .init_resource::<ReadyFrames>()
+    .add_observer(
+        |trigger: Trigger<PortalReady>,
+         frame_count: Res<PortalFrameCount>,
+         mut ready_frames: ResMut<ReadyFrames>| {
+            ready_frames.0.insert(trigger.entity(), frame_count.get());
+        },
+    );
+
+    // (render_interval, render_phase) of each portal
+    let settings = [(1, 0), (3, 0), (3, 1)];
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let portals: Vec<Entity> = settings
+        .iter()
+        .map(|&(interval, phase)| {
+            let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+            world
+                .spawn(
+                    Portal::new(primary_camera, target)
+                        .with_render_interval(interval)
+                        .with_render_phase(Some(phase)),
+                )
+                .id()
+        })
+        .collect();
+    world.flush();
+
+    let mut last_rendered = [None; 3];
+    for _ in 0..9 {
+        app.update();
+        let frame = app.world().resource::<PortalFrameCount>().get();
+
+        for (i, (&portal, &(interval, phase))) in portals.iter().zip(&settings).enumerate() {
+            let is_rendered = frame.wrapping_add(phase) % interval == 0;
+            if is_rendered {
+                last_rendered[i] = Some(frame);
+            }
+
+            let camera = common::linked_camera(&app, portal);
+            assert_eq!(
+                app.world().get::<Camera>(camera).unwrap().is_active,
+                is_rendered,
+                "portal with interval {interval} and phase {phase} on frame {frame}"
+            );
+            assert_eq!(
+                app.world()
+                    .get::<PortalRenderedFrame>(portal)
+                    .unwrap()
+                    .get(),
+                last_rendered[i],
+                "portal with interval {interval} and phase {phase} on frame {frame}"
+            );
+        }
+    }
+
+    // Portals are ready once they've been rendered twice
+    let ready_frames = &app.world().resource::<ReadyFrames>().0;
+    assert_eq!(ready_frames.get(&portals[0]), Some(&2));
+    assert_eq!(ready_frames.get(&portals[1]), Some(&6));
+    assert_eq!(ready_frames.get(&portals[2]), Some(&5));
+}
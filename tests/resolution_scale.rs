@@ -0,0 +1,130 @@
+mod common;
+
+use bevy::{
+    pbr::DirectionalLightShadowMap, prelude::*, render::camera::Viewport, window::ExitCondition,
+};
+use bevy_easy_portals::{
+    camera::{PortalImage, PortalImagesResized},
+    Portal,
+};
+
+/// Spawns a [`Portal`] with the given `resolution_scale`, viewed by a camera with `camera`'s
+/// settings, returning the size of its image.
+fn portal_image_size(app: &mut App, camera: Camera, resolution_scale: f32) -> UVec2 {
+    let world = app.world_mut();
+    let primary_camera = world.spawn((Camera3d::default(), camera)).id();
+    let target = world.spawn(Transform::from_xyz(10.0, 0.0, 0.0)).id();
+    let portal = world
+        .spawn(Portal::new(primary_camera, target).with_resolution_scale(resolution_scale))
+        .id();
+    world.flush();
+
+    let portal_image = world
+        .get::<PortalImage>(portal)
+        .expect("portal should have an image");
+    world
+        .resource::<Assets<Image>>()
+        .get(&portal_image.0)
+        .expect("portal image should exist")
+        .size()
+}
+
+#[test]
+fn resolution_scale_of_two_doubles_image_resolution() {
+    let mut app = common::portal_app();
+    let viewport_camera = || Camera {
+        viewport: Some(Viewport {
+            physical_size: UVec2::new(640, 360),
+            ..default()
+        }),
+        ..default()
+    };
+
+    assert_eq!(
+        portal_image_size(&mut app, viewport_camera(), 1.0),
+        UVec2::new(640, 360)
+    );
+    assert_eq!(
+        portal_image_size(&mut app, viewport_camera(), 2.0),
+        UVec2::new(1280, 720),
+        "a supersampled image should be twice the size of the viewport"
+    );
+
+    // Without a viewport, the image is sized to the primary window instead
+    let window_size = app
+        .world_mut()
+        .query::<&Window>()
+        .single(app.world())
+        .physical_size();
+    assert_eq!(
+        portal_image_size(&mut app, Camera::default(), 2.0),
+        window_size * 2
+    );
+}
+
+/// Returns the [`PortalImage`] handle of `portal` along with the size of its image.
+fn portal_image(app: &App, portal: Entity) -> (Handle<Image>, UVec2) {
+    let handle = app.world().get::<PortalImage>(portal).unwrap().0.clone();
+    let size = app
+        .world()
+        .resource::<Assets<Image>>()
+        .get(&handle)
+        .expect("portal image should exist")
+        .size();
+    (handle, size)
+}
+
+/// Applies `change` to the [`Portal`] of `portal`, then updates `app`, returning whether
+/// [`PortalImagesResized`] was sent.
+fn change_portal(app: &mut App, portal: Entity, change: impl FnOnce(&mut Portal)) -> bool {
+    change(&mut app.world_mut().get_mut::<Portal>(portal).unwrap());
+    app.update();
+    !app.world()
+        .resource::<Events<PortalImagesResized>>()
+        .is_empty()
+}
+
+#[test]
+fn changing_resolution_at_runtime_resizes_image_in_place() {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_resource::<DirectionalLightShadowMap>();
+    let (_, portal) = common::spawn_portal(&mut app, ());
+    // Let any events sent while setting up the portal expire
+    for _ in 0..3 {
+        app.update();
+    }
+    let (handle, size) = portal_image(&app, portal);
+
+    assert!(
+        change_portal(&mut app, portal, |portal| portal.resolution_scale = 0.5),
+        "changing the resolution scale should send PortalImagesResized"
+    );
+    let (scaled_handle, scaled_size) = portal_image(&app, portal);
+    assert_eq!(
+        scaled_handle, handle,
+        "the image should be resized in place"
+    );
+    assert_eq!(scaled_size, size / 2);
+
+    for _ in 0..2 {
+        app.update();
+    }
+    let fixed_resolution = UVec2::new(300, 200);
+    assert!(
+        change_portal(&mut app, portal, |portal| {
+            portal.fixed_resolution = Some(fixed_resolution);
+        }),
+        "changing the fixed resolution should send PortalImagesResized"
+    );
+    let (fixed_handle, fixed_size) = portal_image(&app, portal);
+    assert_eq!(fixed_handle, handle, "the image should be resized in place");
+    assert_eq!(fixed_size, fixed_resolution);
+}
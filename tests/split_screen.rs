@@ -0,0 +1,122 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::{
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    render::camera::{ManualTextureViews, Viewport},
+    window::{ExitCondition, PrimaryWindow, WindowResized},
+};
+use bevy_easy_portals::camera::{PortalCameraPlugin, PortalImage};
+
+/// Creates an [`App`] like [`common::portal_app`], but with images resized as soon as their window
+/// or viewport changes.
+fn split_screen_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        PortalCameraPlugin {
+            resize_debounce: Duration::ZERO,
+            ..default()
+        },
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_resource::<ManualTextureViews>()
+    .init_resource::<DirectionalLightShadowMap>();
+    app.world_mut().spawn((Window::default(), PrimaryWindow));
+    app
+}
+
+/// Returns a viewport covering the left or right half of a window of the given `size`.
+fn half_viewport(size: UVec2, right: bool) -> Viewport {
+    let half_width = size.x / 2;
+    Viewport {
+        physical_position: UVec2::new(if right { half_width } else { 0 }, 0),
+        physical_size: UVec2::new(half_width, size.y),
+        ..default()
+    }
+}
+
+fn portal_image_size(app: &App, portal: Entity) -> UVec2 {
+    let portal_image = app
+        .world()
+        .get::<PortalImage>(portal)
+        .expect("portal should have an image");
+    app.world()
+        .resource::<Assets<Image>>()
+        .get(&portal_image.0)
+        .expect("portal image should exist")
+        .size()
+}
+
+#[test]
+fn portal_images_match_split_screen_viewports() {
+    let mut app = split_screen_app();
+    let window_size = UVec2::new(1280, 720);
+    let portals = [false, true].map(|right| {
+        common::spawn_portal(
+            &mut app,
+            Camera {
+                viewport: Some(half_viewport(window_size, right)),
+                ..default()
+            },
+        )
+    });
+    app.update();
+
+    for (_, portal) in portals {
+        assert_eq!(portal_image_size(&app, portal), UVec2::new(640, 720));
+    }
+
+    // Resize the window, updating the viewports to match like a split-screen game would
+    let window_size = UVec2::new(1600, 900);
+    let world = app.world_mut();
+    let (window, mut primary_window) = world
+        .query_filtered::<(Entity, &mut Window), With<PrimaryWindow>>()
+        .single_mut(world);
+    primary_window
+        .resolution
+        .set_physical_resolution(window_size.x, window_size.y);
+    world.send_event(WindowResized {
+        window,
+        width: window_size.x as f32,
+        height: window_size.y as f32,
+    });
+    for (right, (primary_camera, _)) in [false, true].into_iter().zip(portals) {
+        world.get_mut::<Camera>(primary_camera).unwrap().viewport =
+            Some(half_viewport(window_size, right));
+    }
+    app.update();
+    app.update();
+
+    for (_, portal) in portals {
+        assert_eq!(
+            portal_image_size(&app, portal),
+            UVec2::new(800, 900),
+            "images should be sized to the new viewports, not the window"
+        );
+    }
+
+    // Viewports can also change without the window being resized
+    let (left_camera, left_portal) = portals[0];
+    app.world_mut()
+        .get_mut::<Camera>(left_camera)
+        .unwrap()
+        .viewport
+        .as_mut()
+        .unwrap()
+        .physical_size = UVec2::new(400, 900);
+    app.update();
+    app.update();
+
+    assert_eq!(portal_image_size(&app, left_portal), UVec2::new(400, 900));
+    assert_eq!(portal_image_size(&app, portals[1].1), UVec2::new(800, 900));
+}
@@ -0,0 +1,188 @@
+#![cfg(feature = "teleport")]
+
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    pbr::DirectionalLightShadowMap,
+    prelude::*,
+    render::{primitives::Aabb, render_resource::Face},
+    window::ExitCondition,
+};
+use bevy_easy_portals::{
+    teleport::{PortalTeleport, PortalTeleportPlugin, Teleportable},
+    Portal,
+};
+
+/// Creates an [`App`] that can be updated, with teleportation enabled.
+fn teleport_app() -> App {
+    let mut app = common::portal_app();
+    app.add_plugins((
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        },
+        TransformPlugin,
+        PortalTeleportPlugin,
+    ))
+    .init_resource::<DirectionalLightShadowMap>();
+    app
+}
+
+/// Spawns a 2x2 [`Portal`] at `translation`, facing +Z, whose target is at `(10, 0, 0)` and rotated
+/// a quarter turn around the y-axis, returning the portal entity.
+fn spawn_portal(app: &mut App, translation: Vec3, cull_mode: Option<Face>) -> Entity {
+    let world = app.world_mut();
+    let primary_camera = world.spawn(Camera3d::default()).id();
+    let target = world
+        .spawn(Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(Quat::from_rotation_y(FRAC_PI_2)))
+        .id();
+    world
+        .spawn((
+            Transform::from_translation(translation),
+            Aabb::from_min_max(Vec3::new(-1.0, -1.0, 0.0), Vec3::new(1.0, 1.0, 0.0)),
+            Portal::new(primary_camera, target).with_cull_mode(cull_mode),
+        ))
+        .id()
+}
+
+/// Spawns a [`Teleportable`] entity at `start`, then moves it to `end` in the next frame, returning
+/// the entity along with the [`PortalTeleport`]s sent in that frame.
+fn move_teleportable(app: &mut App, start: Vec3, end: Vec3) -> (Entity, Vec<PortalTeleport>) {
+    let entity = app
+        .world_mut()
+        .spawn((Teleportable, Transform::from_translation(start)))
+        .id();
+    app.update();
+    app.world_mut()
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation = end;
+    app.update();
+
+    let teleports = app
+        .world()
+        .resource::<Events<PortalTeleport>>()
+        .iter_current_update_events()
+        .copied()
+        .collect();
+    (entity, teleports)
+}
+
+#[test]
+fn crossing_portal_teleports_to_target() {
+    let mut app = teleport_app();
+    let portal = spawn_portal(&mut app, Vec3::ZERO, Some(Face::Back));
+
+    let start = Vec3::new(0.5, 0.0, 1.0);
+    let end = Vec3::new(0.5, 0.0, -1.0);
+    let (entity, teleports) = move_teleportable(&mut app, start, end);
+
+    // 0.5 to the right and 1 behind the portal, relative to the rotated target
+    let expected = Vec3::new(9.0, 0.0, -0.5);
+    let transform = app.world().get::<Transform>(entity).unwrap();
+    assert!(
+        transform.translation.abs_diff_eq(expected, 1e-5),
+        "entity should be teleported to {expected}, but is at {}",
+        transform.translation
+    );
+    assert!(transform
+        .rotation
+        .abs_diff_eq(Quat::from_rotation_y(FRAC_PI_2), 1e-5));
+
+    let [teleport] = teleports[..] else {
+        panic!("expected one teleport, got {}", teleports.len());
+    };
+    assert_eq!(teleport.entity, entity);
+    assert_eq!(teleport.portal, portal);
+    assert_eq!(teleport.from.translation, end);
+    assert_eq!(teleport.to, *transform);
+    assert!(teleport
+        .rotation
+        .abs_diff_eq(Quat::from_rotation_y(FRAC_PI_2), 1e-5));
+    assert!((teleport.scale - 1.0).abs() < 1e-5);
+}
+
+#[test]
+fn moving_without_crossing_portal_doesnt_teleport() {
+    for (start, end) in [
+        // Passing in front of the portal, entering and exiting its bounds on the same side
+        (Vec3::new(-3.0, 0.0, 0.5), Vec3::new(3.0, 0.0, 0.5)),
+        // Crossing the portal's plane outside of its bounds
+        (Vec3::new(3.0, 0.0, 1.0), Vec3::new(3.0, 0.0, -1.0)),
+    ] {
+        let mut app = teleport_app();
+        spawn_portal(&mut app, Vec3::ZERO, Some(Face::Back));
+
+        let (entity, teleports) = move_teleportable(&mut app, start, end);
+        assert!(teleports.is_empty(), "moving from {start} to {end}");
+        assert_eq!(
+            app.world().get::<Transform>(entity).unwrap().translation,
+            end
+        );
+    }
+}
+
+#[test]
+fn crossing_culled_face_doesnt_teleport() {
+    let start = Vec3::new(0.0, 0.0, -1.0);
+    let end = Vec3::new(0.0, 0.0, 1.0);
+    for (cull_mode, is_teleported) in [(Some(Face::Back), false), (None, true)] {
+        let mut app = teleport_app();
+        spawn_portal(&mut app, Vec3::ZERO, cull_mode);
+
+        let (_, teleports) = move_teleportable(&mut app, start, end);
+        assert_eq!(
+            !teleports.is_empty(),
+            is_teleported,
+            "crossing the back face with a cull_mode of {cull_mode:?}"
+        );
+    }
+}
+
+#[test]
+fn overlapping_portals_teleport_through_first_reached() {
+    let mut app = teleport_app();
+    let behind = spawn_portal(&mut app, Vec3::new(0.0, 0.0, -1.0), Some(Face::Back));
+    let front = spawn_portal(&mut app, Vec3::ZERO, Some(Face::Back));
+    assert_ne!(behind, front);
+
+    let (_, teleports) = move_teleportable(
+        &mut app,
+        Vec3::new(0.0, 0.0, 1.0),
+        Vec3::new(0.0, 0.0, -2.0),
+    );
+    let [teleport] = teleports[..] else {
+        panic!("expected one teleport, got {}", teleports.len());
+    };
+    assert_eq!(teleport.portal, front);
+}
+
+#[test]
+fn entities_with_parent_arent_teleported() {
+    let mut app = teleport_app();
+    spawn_portal(&mut app, Vec3::ZERO, Some(Face::Back));
+    let parent = app.world_mut().spawn(Transform::default()).id();
+
+    let start = Vec3::new(0.0, 0.0, 1.0);
+    let end = Vec3::new(0.0, 0.0, -1.0);
+    let entity = app
+        .world_mut()
+        .spawn((Teleportable, Transform::from_translation(start)))
+        .set_parent(parent)
+        .id();
+    app.update();
+    app.world_mut()
+        .get_mut::<Transform>(entity)
+        .unwrap()
+        .translation = end;
+    app.update();
+
+    assert!(app.world().resource::<Events<PortalTeleport>>().is_empty());
+    assert_eq!(
+        app.world().get::<Transform>(entity).unwrap().translation,
+        end
+    );
+}